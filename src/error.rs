@@ -0,0 +1,16 @@
+//! Error handling shared by the modules of the application
+
+use std::error::Error;
+
+/// Formats an error, followed by all the errors that caused it
+pub fn error_chain(error: &dyn Error) -> String {
+    let mut message = error.to_string();
+
+    let mut source = error.source();
+    while let Some(error) = source {
+        message.push_str(&format!(": {error}"));
+        source = error.source();
+    }
+
+    message
+}
@@ -0,0 +1,66 @@
+//! Exporting models to files
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// A file format that models can be exported to
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// 3D Manufacturing Format
+    ThreeMf,
+
+    /// Stereolithography
+    Stl,
+
+    /// Wavefront OBJ
+    Obj,
+}
+
+impl Format {
+    /// All supported formats
+    pub const ALL: [Self; 3] = [Self::ThreeMf, Self::Stl, Self::Obj];
+
+    /// The file extension of the format
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::ThreeMf => "3mf",
+            Self::Stl => "stl",
+            Self::Obj => "obj",
+        }
+    }
+
+    /// The name of the format, as shown to the user
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ThreeMf => "3MF",
+            Self::Stl => "STL",
+            Self::Obj => "OBJ",
+        }
+    }
+}
+
+/// Export the mesh of a model to a file
+///
+/// The mesh is exported as it is displayed, i.e. triangulated with the same
+/// tolerance. The file is created, if it doesn't exist, and overwritten
+/// otherwise. Its extension is not checked against the format.
+pub fn export(
+    model: &fj_interop::Model,
+    format: Format,
+    path: &Path,
+) -> Result<(), fj_export::Error> {
+    let mut file = BufWriter::new(File::create(path)?);
+
+    match format {
+        Format::ThreeMf => fj_export::export_3mf(&model.mesh, &mut file)?,
+        Format::Stl => fj_export::export_stl(&model.mesh, &mut file)?,
+        Format::Obj => fj_export::export_obj(&model.mesh, &mut file)?,
+    }
+
+    // Flush explicitly, as errors would go unnoticed when the writer is
+    // dropped.
+    file.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,589 @@
+//! Bounding volume hierarchy for ray casting against a mesh
+
+use fj_interop::Mesh;
+use fj_math::{Point, Scalar, Vector};
+
+/// A bounding volume hierarchy over the triangles of a mesh
+///
+/// Built once per model, this makes casting a ray against the mesh take
+/// logarithmic instead of linear time in the number of triangles.
+#[derive(Debug)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    triangles: Vec<[[f64; 3]; 3]>,
+
+    /// The index of each triangle in the original mesh
+    ///
+    /// Building the hierarchy reorders the triangles, so that each leaf node
+    /// refers to a contiguous range of them.
+    mesh_indices: Vec<usize>,
+}
+
+impl Bvh {
+    /// The maximum number of triangles in a leaf node
+    const MAX_LEAF_SIZE: usize = 4;
+
+    /// Build a bounding volume hierarchy over the triangles of a mesh
+    pub fn new(mesh: &Mesh<Point<3>>) -> Self {
+        let triangles: Vec<[[f64; 3]; 3]> =
+            mesh.triangles()
+                .map(|triangle| {
+                    triangle.inner.points().map(|point| {
+                        point.coords.components.map(Scalar::into_f64)
+                    })
+                })
+                .collect();
+
+        let mut bvh = Self {
+            nodes: Vec::new(),
+            mesh_indices: (0..triangles.len()).collect(),
+            triangles,
+        };
+
+        if !bvh.triangles.is_empty() {
+            bvh.build(0, bvh.triangles.len());
+        }
+
+        bvh
+    }
+
+    /// Find the closest intersection of a ray with the mesh
+    ///
+    /// `direction` must be normalized, for [`Hit::distance`] to be the actual
+    /// distance from `origin`.
+    pub fn cast_ray(
+        &self,
+        origin: Point<3>,
+        direction: Vector<3>,
+    ) -> Option<Hit> {
+        let origin = origin.coords.components.map(Scalar::into_f64);
+        let direction = direction.components.map(Scalar::into_f64);
+        let inverse_direction = direction.map(|d| 1. / d);
+
+        let mut closest: Option<Hit> = None;
+        let mut stack = Vec::new();
+
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let max_distance =
+                closest.map_or(f64::INFINITY, |hit| hit.distance);
+
+            if node
+                .aabb
+                .intersect_ray(origin, inverse_direction, max_distance)
+                .is_none()
+            {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Leaf { start, end } => {
+                    for i in start..end {
+                        let Some(t) = intersect_triangle(
+                            origin,
+                            direction,
+                            &self.triangles[i],
+                        ) else {
+                            continue;
+                        };
+
+                        if closest.is_none_or(|hit| t < hit.distance) {
+                            let point = [0, 1, 2]
+                                .map(|axis| origin[axis] + direction[axis] * t);
+
+                            closest = Some(Hit {
+                                point: Point::from(point),
+                                triangle_index: self.mesh_indices[i],
+                                distance: t,
+                            });
+                        }
+                    }
+                }
+                NodeKind::Interior { left, right } => {
+                    // Visit the closer child first, so hits found there can
+                    // prune the other one.
+                    let distance = |child: usize| {
+                        self.nodes[child]
+                            .aabb
+                            .intersect_ray(
+                                origin,
+                                inverse_direction,
+                                max_distance,
+                            )
+                            .unwrap_or(f64::INFINITY)
+                    };
+
+                    if distance(left) < distance(right) {
+                        stack.push(right);
+                        stack.push(left);
+                    } else {
+                        stack.push(left);
+                        stack.push(right);
+                    }
+                }
+            }
+        }
+
+        closest
+    }
+
+    /// Recursively build the node for the triangles in `start..end`
+    ///
+    /// Returns the index of the node.
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let index = self.nodes.len();
+
+        let aabb = self.triangles[start..end]
+            .iter()
+            .flatten()
+            .fold(NodeAabb::EMPTY, |aabb, point| aabb.include(*point));
+        self.nodes.push(Node {
+            aabb,
+            kind: NodeKind::Leaf { start, end },
+        });
+
+        if end - start <= Self::MAX_LEAF_SIZE {
+            return index;
+        }
+
+        // Split along the axis in which the triangle centroids are spread the
+        // furthest, at the median centroid.
+        let centroid = |triangle: &[[f64; 3]; 3], axis: usize| {
+            triangle.iter().map(|point| point[axis]).sum::<f64>() / 3.
+        };
+        let centroid_bounds = self.triangles[start..end].iter().fold(
+            NodeAabb::EMPTY,
+            |aabb, triangle| {
+                aabb.include([0, 1, 2].map(|axis| centroid(triangle, axis)))
+            },
+        );
+        let extent = [0, 1, 2]
+            .map(|axis| centroid_bounds.max[axis] - centroid_bounds.min[axis]);
+        let axis = (0..3)
+            .max_by(|&a, &b| extent[a].total_cmp(&extent[b]))
+            .unwrap_or(0);
+
+        if extent[axis] <= 0. {
+            // All centroids are in the same place. There's no sensible way
+            // to split.
+            return index;
+        }
+
+        let middle = start + (end - start) / 2;
+
+        let mut order: Vec<usize> = (start..end).collect();
+        order.select_nth_unstable_by(middle - start, |&a, &b| {
+            centroid(&self.triangles[a], axis)
+                .total_cmp(&centroid(&self.triangles[b], axis))
+        });
+        let triangles =
+            order.iter().map(|&i| self.triangles[i]).collect::<Vec<_>>();
+        let mesh_indices = order
+            .iter()
+            .map(|&i| self.mesh_indices[i])
+            .collect::<Vec<_>>();
+        self.triangles[start..end].copy_from_slice(&triangles);
+        self.mesh_indices[start..end].copy_from_slice(&mesh_indices);
+
+        let left = self.build(start, middle);
+        let right = self.build(middle, end);
+        self.nodes[index].kind = NodeKind::Interior { left, right };
+
+        index
+    }
+}
+
+/// The result of casting a ray against a mesh
+#[derive(Clone, Copy, Debug)]
+pub struct Hit {
+    /// The point where the ray hits the mesh
+    pub point: Point<3>,
+
+    /// The index of the triangle that was hit, in the order of
+    /// [`Mesh::triangles`]
+    pub triangle_index: usize,
+
+    /// The distance from the origin of the ray to the hit
+    pub distance: f64,
+}
+
+#[derive(Debug)]
+struct Node {
+    aabb: NodeAabb,
+    kind: NodeKind,
+}
+
+#[derive(Debug)]
+enum NodeKind {
+    Leaf { start: usize, end: usize },
+    Interior { left: usize, right: usize },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct NodeAabb {
+    min: [f64; 3],
+    max: [f64; 3],
+}
+
+impl NodeAabb {
+    const EMPTY: Self = Self {
+        min: [f64::INFINITY; 3],
+        max: [f64::NEG_INFINITY; 3],
+    };
+
+    fn include(self, point: [f64; 3]) -> Self {
+        Self {
+            min: [0, 1, 2].map(|axis| self.min[axis].min(point[axis])),
+            max: [0, 1, 2].map(|axis| self.max[axis].max(point[axis])),
+        }
+    }
+
+    /// Intersect a ray with the bounding box, using the slab method
+    ///
+    /// Returns the distance at which the ray enters the box, if it does so
+    /// before `max_distance`.
+    fn intersect_ray(
+        &self,
+        origin: [f64; 3],
+        inverse_direction: [f64; 3],
+        max_distance: f64,
+    ) -> Option<f64> {
+        let mut t_min = 0_f64;
+        let mut t_max = max_distance;
+
+        for axis in 0..3 {
+            if inverse_direction[axis].is_infinite() {
+                // The ray is parallel to the slab, so it's either between its
+                // planes along its whole length, or never. Computing the
+                // distances would result in NaN, for rays that lie exactly in
+                // one of the planes.
+                if origin[axis] < self.min[axis]
+                    || origin[axis] > self.max[axis]
+                {
+                    return None;
+                }
+                continue;
+            }
+
+            let t1 = (self.min[axis] - origin[axis]) * inverse_direction[axis];
+            let t2 = (self.max[axis] - origin[axis]) * inverse_direction[axis];
+
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+        }
+
+        (t_min <= t_max).then_some(t_min)
+    }
+}
+
+/// Intersect a ray with a triangle, using the Möller–Trumbore algorithm
+///
+/// Both sides of the triangle are considered. Returns the distance along the
+/// ray, in units of `direction`.
+fn intersect_triangle(
+    origin: [f64; 3],
+    direction: [f64; 3],
+    [a, b, c]: &[[f64; 3]; 3],
+) -> Option<f64> {
+    const EPSILON: f64 = 1e-12;
+
+    let edge_1 = sub(*b, *a);
+    let edge_2 = sub(*c, *a);
+
+    let p = cross(direction, edge_2);
+    let determinant = dot(edge_1, p);
+    if determinant.abs() < EPSILON {
+        // The ray is parallel to the triangle.
+        return None;
+    }
+    let inverse_determinant = 1. / determinant;
+
+    let s = sub(origin, *a);
+    let u = dot(s, p) * inverse_determinant;
+    if !(0. ..=1.).contains(&u) {
+        return None;
+    }
+
+    let q = cross(s, edge_1);
+    let v = dot(direction, q) * inverse_determinant;
+    if v < 0. || u + v > 1. {
+        return None;
+    }
+
+    let t = dot(edge_2, q) * inverse_determinant;
+    (t >= 0.).then_some(t)
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use fj_interop::{Color, Mesh};
+    use fj_math::{Point, Scalar, Triangle, Vector};
+
+    use super::{intersect_triangle, Bvh};
+
+    #[test]
+    fn closest_hit_matches_brute_force() {
+        let mesh = spheres();
+        let bvh = Bvh::new(&mesh);
+        let mut random = Random(1);
+        let mut num_hits = 0;
+
+        for _ in 0..500 {
+            // Rays from all around the spheres, aimed at points near them
+            let origin = random.point(12.);
+            let target = random.point(3.);
+            let direction = (target - origin).normalize();
+
+            let expected = brute_force(&mesh, origin, direction);
+            let hit = bvh.cast_ray(origin, direction);
+
+            match (expected, hit) {
+                (None, None) => {}
+                (Some((_, distance)), Some(hit)) => {
+                    num_hits += 1;
+                    assert!((hit.distance - distance).abs() < 1e-9);
+                    // Rays through an edge hit two triangles at the same
+                    // distance, so only check that the reported one is hit.
+                    let triangle = points(&mesh, hit.triangle_index);
+                    let hit_distance =
+                        intersect(origin, direction, &triangle).unwrap();
+                    assert!((hit_distance - distance).abs() < 1e-9);
+
+                    let expected_point = origin + direction * distance;
+                    assert!(
+                        (hit.point - expected_point).magnitude().into_f64()
+                            < 1e-9
+                    );
+                }
+                (expected, hit) => {
+                    panic!("Expected {expected:?}, got {hit:?}")
+                }
+            }
+        }
+
+        // Make sure that the rays don't all miss.
+        assert!(num_hits > 100, "{num_hits}");
+    }
+
+    #[test]
+    fn triangle_index_refers_to_the_original_mesh() {
+        // Building the hierarchy reorders the triangles. Add a triangle last,
+        // far from the spheres, which ends up somewhere in the middle.
+        let mut mesh = spheres();
+        push_triangle(
+            &mut mesh,
+            [[-1., -1., -20.], [1., -1., -20.], [0., 1., -20.]],
+        );
+        let index = mesh.triangles().count() - 1;
+        let bvh = Bvh::new(&mesh);
+        assert_ne!(bvh.mesh_indices.last(), Some(&index));
+
+        let hit = bvh
+            .cast_ray(Point::from([0., 0., -30.]), Vector::from([0., 0., 1.]))
+            .unwrap();
+
+        assert_eq!(hit.triangle_index, index);
+        assert!((hit.distance - 10.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rays_that_miss_return_nothing() {
+        let mesh = spheres();
+        let bvh = Bvh::new(&mesh);
+
+        // Pointing away from the spheres
+        let origin = Point::from([0., 0., 10.]);
+        assert!(bvh.cast_ray(origin, Vector::from([0., 0., 1.])).is_none());
+
+        // Passing by all of them
+        let origin = Point::from([-10., 0., 5.]);
+        let direction = Vector::from([1., 0., 0.]);
+        assert!(brute_force(&mesh, origin, direction).is_none());
+        assert!(bvh.cast_ray(origin, direction).is_none());
+
+        // An empty mesh
+        let bvh = Bvh::new(&Mesh::new());
+        let origin = Point::from([0., 0., 10.]);
+        assert!(bvh.cast_ray(origin, Vector::from([0., 0., -1.])).is_none());
+    }
+
+    #[test]
+    fn rays_parallel_to_slabs_match_brute_force() {
+        // A flat grid, so the bounding boxes of all nodes share the slab at
+        // z = 0, and their other slabs lie on the grid lines.
+        let mut mesh = Mesh::new();
+        for x in 0..8 {
+            for y in 0..8 {
+                let [x, y] = [x, y].map(f64::from);
+                push_triangle(
+                    &mut mesh,
+                    [[x, y, 0.], [x + 1., y, 0.], [x + 1., y + 1., 0.]],
+                );
+                push_triangle(
+                    &mut mesh,
+                    [[x, y, 0.], [x + 1., y + 1., 0.], [x, y + 1., 0.]],
+                );
+            }
+        }
+        let bvh = Bvh::new(&mesh);
+
+        // Rays along the z axis, starting on grid lines, between them, and
+        // exactly on the border of the grid
+        let down = Vector::from([0., 0., -1.]);
+        for x in [0., 0.5, 1., 3.25, 4., 7.5, 8.] {
+            for y in [0., 0.5, 2., 6.75, 8.] {
+                let origin = Point::from([x, y, 5.]);
+
+                let expected = brute_force(&mesh, origin, down);
+                let hit = bvh.cast_ray(origin, down);
+
+                assert_eq!(
+                    expected.map(|(_, distance)| distance),
+                    hit.map(|hit| hit.distance),
+                    "Ray from {origin:?}"
+                );
+                assert!(hit.is_some(), "Ray from {origin:?}");
+            }
+        }
+
+        // Rays that lie in the plane of the grid, along and across its lines
+        for (origin, direction) in [
+            ([-1., 4., 0.], [1., 0., 0.]),
+            ([4., -1., 0.], [0., 1., 0.]),
+            ([-1., 0., 0.], [1., 0., 0.]),
+        ] {
+            let origin = Point::from(origin);
+            let direction = Vector::from(direction);
+
+            assert!(brute_force(&mesh, origin, direction).is_none());
+            assert!(bvh.cast_ray(origin, direction).is_none());
+        }
+
+        // Rays parallel to the grid, just above and below it
+        for z in [1e-3, -1e-3] {
+            let origin = Point::from([-1., 4., z]);
+            let direction = Vector::from([1., 0., 0.]);
+
+            assert!(bvh.cast_ray(origin, direction).is_none());
+        }
+    }
+
+    /// Three spheres of different sizes, with a few hundred triangles each
+    fn spheres() -> Mesh<Point<3>> {
+        let mut mesh = Mesh::new();
+
+        for (center, radius) in [
+            ([0., 0., 0.], 1.),
+            ([2.5, 0.5, -0.5], 0.75),
+            ([-1., 2., 1.5], 1.25),
+        ] {
+            let rings = 12;
+            let segments = 24;
+            let point = |ring: u32, segment: u32| {
+                let theta = PI * f64::from(ring) / f64::from(rings);
+                let phi = 2. * PI * f64::from(segment) / f64::from(segments);
+                [
+                    center[0] + radius * theta.sin() * phi.cos(),
+                    center[1] + radius * theta.sin() * phi.sin(),
+                    center[2] + radius * theta.cos(),
+                ]
+            };
+
+            for ring in 0..rings {
+                for segment in 0..segments {
+                    let a = point(ring, segment);
+                    let b = point(ring + 1, segment);
+                    let c = point(ring + 1, segment + 1);
+                    let d = point(ring, segment + 1);
+
+                    push_triangle(&mut mesh, [a, b, c]);
+                    push_triangle(&mut mesh, [a, c, d]);
+                }
+            }
+        }
+
+        mesh
+    }
+
+    fn push_triangle(mesh: &mut Mesh<Point<3>>, points: [[f64; 3]; 3]) {
+        // Triangles at the poles of the spheres are degenerate.
+        if let Ok(triangle) = Triangle::from_points(points) {
+            mesh.push_triangle(triangle, Color::default());
+        }
+    }
+
+    fn points(mesh: &Mesh<Point<3>>, index: usize) -> [[f64; 3]; 3] {
+        let triangle = mesh.triangles().nth(index).unwrap();
+        triangle
+            .inner
+            .points()
+            .map(|point| point.coords.components.map(Scalar::into_f64))
+    }
+
+    fn intersect(
+        origin: Point<3>,
+        direction: Vector<3>,
+        triangle: &[[f64; 3]; 3],
+    ) -> Option<f64> {
+        intersect_triangle(
+            origin.coords.components.map(Scalar::into_f64),
+            direction.components.map(Scalar::into_f64),
+            triangle,
+        )
+    }
+
+    /// Find the closest hit by testing every triangle
+    fn brute_force(
+        mesh: &Mesh<Point<3>>,
+        origin: Point<3>,
+        direction: Vector<3>,
+    ) -> Option<(usize, f64)> {
+        (0..mesh.triangles().count())
+            .filter_map(|index| {
+                let distance =
+                    intersect(origin, direction, &points(mesh, index))?;
+                Some((index, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    /// A linear congruential generator, so the tests are reproducible
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> f64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64
+        }
+
+        /// A point in the cube of the given half-size around the origin
+        fn point(&mut self, size: f64) -> Point<3> {
+            Point::from([(); 3].map(|()| (self.next() * 2. - 1.) * size))
+        }
+    }
+}
@@ -0,0 +1,24 @@
+struct Uniforms {
+    origin: vec2<f32>,
+    _padding: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@group(0) @binding(1)
+var source: texture_2d<f32>;
+
+@vertex
+fn vertex(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // The scissor rect limits drawing to the area of the widget.
+    return vec4<f32>(fullscreen_corner(index), 0.0, 1.0);
+}
+
+@fragment
+fn fragment(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let size = vec2<i32>(textureDimensions(source));
+    let coords = vec2<i32>(floor(position.xy - uniforms.origin));
+
+    return textureLoad(source, clamp(coords, vec2<i32>(0), size - 1), 0);
+}
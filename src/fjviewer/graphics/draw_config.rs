@@ -1,11 +1,32 @@
+use super::{Lighting, Material};
+
 /// High level configuration for rendering the active model
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct DrawConfig {
     /// Toggle for displaying the shaded model
     pub draw_model: bool,
 
     /// Toggle for displaying the wireframe model
     pub draw_mesh: bool,
+
+    /// Toggle for displaying crease, boundary and silhouette edges
+    pub draw_edges: bool,
+
+    /// The angle between two triangles, in radians, above which the edge
+    /// between them is drawn as a crease
+    pub crease_angle: f64,
+
+    /// The width of the edges, in logical pixels
+    pub edge_width: f32,
+
+    /// How the shaded model is lit
+    pub lighting: Lighting,
+
+    /// The material that the model is shaded with
+    pub material: Material,
+
+    /// What is drawn behind the model
+    pub background: Background,
 }
 
 impl Default for DrawConfig {
@@ -13,6 +34,44 @@ impl Default for DrawConfig {
         Self {
             draw_model: true,
             draw_mesh: false,
+            draw_edges: true,
+            crease_angle: 30_f64.to_radians(),
+            edge_width: 1.5,
+            lighting: Lighting::default(),
+            material: Material::default(),
+            background: Background::default(),
+        }
+    }
+}
+
+impl DrawConfig {
+    /// Indicate whether the environment map is shown, or lights the model
+    pub fn uses_environment(&self) -> bool {
+        self.background == Background::Environment
+            || (self.draw_model && self.lighting.environment > 0.)
+    }
+}
+
+/// What is drawn behind the model
+///
+/// Colors are linear RGB.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Background {
+    /// A single color
+    Solid([f32; 3]),
+
+    /// A vertical gradient, from the top to the bottom of the view
+    Gradient { top: [f32; 3], bottom: [f32; 3] },
+
+    /// The bundled environment map, which turns along with the camera
+    Environment,
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self::Gradient {
+            top: [0.3, 0.35, 0.45],
+            bottom: [0.85, 0.85, 0.85],
         }
     }
 }
@@ -1,34 +1,53 @@
 use iced_wgpu::wgpu;
 use super::{
-    geometries::{Geometries, Geometry},
+    geometries::{FeatureEdges, Geometries, Geometry, Indices},
     pipelines::{Pipeline, Pipelines},
 };
 
 pub struct Drawables<'r> {
     pub model: Drawable<'r>,
-    pub mesh: Option<Drawable<'r>>,
+    pub mesh: Drawable<'r>,
+    pub feature_edges: FeatureEdgesDrawable<'r>,
 }
 
 impl<'r> Drawables<'r> {
     pub fn new(geometries: &'r Geometries, pipelines: &'r Pipelines) -> Self {
-        let model = Drawable::new(&geometries.mesh, &pipelines.model);
-        let mesh = pipelines
-            .mesh
-            .as_ref()
-            .map(|pipeline| Drawable::new(&geometries.mesh, pipeline));
+        let geometry = &geometries.mesh;
 
-        Self { model, mesh }
+        let model =
+            Drawable::new(geometry, &geometry.triangles, &pipelines.model);
+        let mesh = Drawable::new(geometry, &geometry.edges, &pipelines.mesh);
+
+        let feature_edges = FeatureEdgesDrawable {
+            edges: &geometries.feature_edges,
+            pipeline: &pipelines.feature_edges,
+        };
+
+        Self {
+            model,
+            mesh,
+            feature_edges,
+        }
     }
 }
 
 pub struct Drawable<'a> {
     pub geometry: &'a Geometry,
+    pub indices: &'a Indices,
     pub pipeline: &'a Pipeline,
 }
 
 impl<'a> Drawable<'a> {
-    fn new(geometry: &'a Geometry, pipeline: &'a Pipeline) -> Self {
-        Self { geometry, pipeline }
+    fn new(
+        geometry: &'a Geometry,
+        indices: &'a Indices,
+        pipeline: &'a Pipeline,
+    ) -> Self {
+        Self {
+            geometry,
+            indices,
+            pipeline,
+        }
     }
 
     pub fn draw<'b>(&self, render_pass: &mut wgpu::RenderPass<'b>)
@@ -38,9 +57,30 @@ impl<'a> Drawable<'a> {
         render_pass.set_pipeline(&self.pipeline.0);
         render_pass.set_vertex_buffer(0, self.geometry.vertex_buffer.slice(..));
         render_pass.set_index_buffer(
-            self.geometry.index_buffer.slice(..),
+            self.indices.buffer.slice(..),
             wgpu::IndexFormat::Uint32,
         );
-        render_pass.draw_indexed(0..self.geometry.num_indices, 0, 0..1);
+        render_pass.draw_indexed(0..self.indices.num_indices, 0, 0..1);
+    }
+}
+
+/// The feature edges, drawn as one quad per edge
+pub struct FeatureEdgesDrawable<'a> {
+    pub edges: &'a FeatureEdges,
+    pub pipeline: &'a Pipeline,
+}
+
+impl<'a> FeatureEdgesDrawable<'a> {
+    pub fn draw<'b>(&self, render_pass: &mut wgpu::RenderPass<'b>)
+    where
+        'a: 'b,
+    {
+        if self.edges.num_edges == 0 {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.pipeline.0);
+        render_pass.set_vertex_buffer(0, self.edges.buffer.slice(..));
+        render_pass.draw(0..6, 0..self.edges.num_edges);
     }
 }
@@ -0,0 +1,307 @@
+//! The environment map, and its convolutions for image-based lighting
+
+use std::borrow::Cow;
+
+use bytemuck::{Pod, Zeroable};
+use iced_wgpu::wgpu;
+use wgpu::util::DeviceExt;
+
+use super::texture::{CubeTexture, LoadTextureError};
+
+/// The environment that the model is placed in
+///
+/// Besides the environment map itself, which is shown by the skybox, it holds
+/// two convolutions of it, that the model is lit with: One for diffuse
+/// lighting, and one for glossy reflections, with a mip level for each step of
+/// roughness.
+#[derive(Debug)]
+pub struct Environment {
+    pub skybox: CubeTexture,
+    pub specular: CubeTexture,
+    pub irradiance: CubeTexture,
+}
+
+impl Environment {
+    /// The size of the faces of [`Environment::specular`], at mip level 0
+    const SPECULAR_SIZE: u32 = 128;
+
+    /// The number of mip levels of [`Environment::specular`]
+    ///
+    /// Level 0 holds sharp reflections, the last level those of a surface
+    /// with a roughness of 1.
+    pub const SPECULAR_MIP_LEVELS: u32 = 5;
+
+    /// The size of the faces of [`Environment::irradiance`]
+    ///
+    /// Irradiance varies slowly with the direction, so this can be tiny.
+    const IRRADIANCE_SIZE: u32 = 16;
+
+    /// Load the environment map that is bundled with the viewer, and compute
+    /// its convolutions
+    pub fn load_bundled(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<Self, LoadTextureError> {
+        let skybox = CubeTexture::from_bytes(
+            device,
+            queue,
+            [
+                include_bytes!("../../../textures/skybox/pos_x.jpg"),
+                include_bytes!("../../../textures/skybox/neg_x.jpg"),
+                include_bytes!("../../../textures/skybox/pos_y.jpg"),
+                include_bytes!("../../../textures/skybox/neg_y.jpg"),
+                include_bytes!("../../../textures/skybox/pos_z.jpg"),
+                include_bytes!("../../../textures/skybox/neg_z.jpg"),
+            ],
+            "environment",
+        )?;
+
+        Ok(Self::new(device, queue, skybox))
+    }
+
+    /// A black environment, which takes no time to set up
+    ///
+    /// It stands in for the actual environment, until that is needed.
+    pub fn placeholder(device: &wgpu::Device) -> Self {
+        let cube =
+            |label| CubeTexture::render_target(device, 1, 1, Some(label));
+
+        Self {
+            skybox: cube("environment placeholder, skybox"),
+            specular: cube("environment placeholder, specular"),
+            irradiance: cube("environment placeholder, irradiance"),
+        }
+    }
+
+    /// Compute the convolutions of an environment map
+    ///
+    /// This renders into every face of every mip level once, so it's only
+    /// meant to be done when the environment changes.
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        skybox: CubeTexture,
+    ) -> Self {
+        let specular = CubeTexture::render_target(
+            device,
+            Self::SPECULAR_SIZE,
+            Self::SPECULAR_MIP_LEVELS,
+            Some("environment, specular"),
+        );
+        let irradiance = CubeTexture::render_target(
+            device,
+            Self::IRRADIANCE_SIZE,
+            1,
+            Some("environment, irradiance"),
+        );
+
+        let prefilter = Prefilter::new(device, &skybox);
+        let mut encoder = device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+
+        for mip_level in 0..Self::SPECULAR_MIP_LEVELS {
+            let roughness =
+                mip_level as f32 / (Self::SPECULAR_MIP_LEVELS - 1) as f32;
+
+            for face in 0..6 {
+                prefilter.draw(
+                    device,
+                    &mut encoder,
+                    &specular.face_view(face, mip_level),
+                    PrefilterUniforms {
+                        face,
+                        mode: PrefilterUniforms::MODE_SPECULAR,
+                        roughness,
+                        _padding: 0.,
+                    },
+                );
+            }
+        }
+        for face in 0..6 {
+            prefilter.draw(
+                device,
+                &mut encoder,
+                &irradiance.face_view(face, 0),
+                PrefilterUniforms {
+                    face,
+                    mode: PrefilterUniforms::MODE_IRRADIANCE,
+                    roughness: 1.,
+                    _padding: 0.,
+                },
+            );
+        }
+
+        queue.submit([encoder.finish()]);
+
+        Self {
+            skybox,
+            specular,
+            irradiance,
+        }
+    }
+}
+
+/// Renders convolutions of an environment map
+struct Prefilter<'a> {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    source: &'a CubeTexture,
+}
+
+impl<'a> Prefilter<'a> {
+    fn new(device: &wgpu::Device, source: &'a CubeTexture) -> Self {
+        let module =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(concat!(
+                    include_str!("fullscreen.wgsl"),
+                    include_str!("prefilter.wgsl"),
+                ))),
+            });
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float {
+                                filterable: true,
+                            },
+                            view_dimension: wgpu::TextureViewDimension::Cube,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(
+                            wgpu::SamplerBindingType::Filtering,
+                        ),
+                        count: None,
+                    },
+                ],
+            });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let pipeline =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: "vertex",
+                    buffers: &[],
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: "fragment",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: CubeTexture::RENDER_TARGET_FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview: None,
+            });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            source,
+        }
+    }
+
+    /// Render a convolution of the source into `target`
+    fn draw(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        uniforms: PrefilterUniforms,
+    ) {
+        // Every draw gets a buffer of its own, as they are all submitted at
+        // once.
+        let uniform_buffer =
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&[uniforms]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(
+                        &self.source.view,
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(
+                        &self.source.sampler,
+                    ),
+                },
+            ],
+        });
+
+        let mut render_pass =
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                ..Default::default()
+            });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct PrefilterUniforms {
+    face: u32,
+    mode: u32,
+    roughness: f32,
+    _padding: f32,
+}
+
+impl PrefilterUniforms {
+    const MODE_SPECULAR: u32 = 0;
+    const MODE_IRRADIANCE: u32 = 1;
+}
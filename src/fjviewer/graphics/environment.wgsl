@@ -0,0 +1,4 @@
+// Cube maps are y-up, while models are z-up.
+fn cube_direction(direction: vec3<f32>) -> vec3<f32> {
+    return vec3<f32>(direction.x, direction.z, -direction.y);
+}
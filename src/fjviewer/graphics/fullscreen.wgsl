@@ -0,0 +1,6 @@
+// The corner of a single triangle that covers the whole target, in normalized
+// device coordinates. Draw it with 3 vertices.
+fn fullscreen_corner(index: u32) -> vec2<f32> {
+    let corner = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return corner * 2.0 - 1.0;
+}
@@ -3,53 +3,209 @@ use iced_wgpu::wgpu;
 
 use wgpu::util::DeviceExt;
 
-use super::vertices::{Vertex, Vertices};
+use super::vertices::{FeatureEdge, Vertex, Vertices};
 
 #[derive(Debug)]
 pub struct Geometries {
     pub mesh: Geometry,
+    pub feature_edges: FeatureEdges,
 }
 
 impl Geometries {
     pub fn new(device: &wgpu::Device, mesh: &Vertices) -> Self {
-        let mesh = Geometry::new(device, mesh.vertices(), mesh.indices());
+        let feature_edges = FeatureEdges::new(device, mesh.feature_edges());
+        let mesh = Geometry::new(
+            device,
+            mesh.vertices(),
+            mesh.indices(),
+            mesh.edges(),
+        );
 
-        Self { mesh }
+        Self {
+            mesh,
+            feature_edges,
+        }
+    }
+
+    /// Replace the geometry with that of another mesh
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        mesh: &Vertices,
+    ) {
+        self.mesh.update(
+            device,
+            queue,
+            mesh.vertices(),
+            mesh.indices(),
+            mesh.edges(),
+        );
+        self.feature_edges
+            .update(device, queue, mesh.feature_edges());
     }
 }
 
 #[derive(Debug)]
 pub struct Geometry {
     pub vertex_buffer: wgpu::Buffer,
-    pub index_buffer: wgpu::Buffer,
-    pub num_indices: u32,
+
+    /// The triangles, as a triangle list
+    pub triangles: Indices,
+
+    /// The edges of the triangles, as a line list
+    pub edges: Indices,
 }
 
 impl Geometry {
     fn new(
         device: &wgpu::Device,
         vertices: &[Vertex],
-        indices: &[u32],
+        triangles: &[u32],
+        edges: &[u32],
     ) -> Self {
         Self {
-            vertex_buffer: device.create_buffer_init(
-                &wgpu::util::BufferInitDescriptor {
-                    label: None,
-                    contents: bytemuck::cast_slice(vertices),
-                    usage: wgpu::BufferUsages::VERTEX,
-                },
+            vertex_buffer: create_buffer(
+                device,
+                bytemuck::cast_slice(vertices),
+                wgpu::BufferUsages::VERTEX,
             ),
-            index_buffer: device.create_buffer_init(
-                &wgpu::util::BufferInitDescriptor {
-                    label: None,
-                    contents: bytemuck::cast_slice(indices),
-                    usage: wgpu::BufferUsages::INDEX,
-                },
+            triangles: Indices::new(device, triangles),
+            edges: Indices::new(device, edges),
+        }
+    }
+
+    /// Replace the vertices and indices
+    ///
+    /// The existing buffers are written to, if they are large enough. They are
+    /// only reallocated, if the new geometry doesn't fit.
+    fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        vertices: &[Vertex],
+        triangles: &[u32],
+        edges: &[u32],
+    ) {
+        write_buffer(
+            device,
+            queue,
+            &mut self.vertex_buffer,
+            bytemuck::cast_slice(vertices),
+            wgpu::BufferUsages::VERTEX,
+        );
+        self.triangles.update(device, queue, triangles);
+        self.edges.update(device, queue, edges);
+    }
+}
+
+/// An index buffer, referring to the vertices of a [`Geometry`]
+#[derive(Debug)]
+pub struct Indices {
+    pub buffer: wgpu::Buffer,
+    pub num_indices: u32,
+}
+
+impl Indices {
+    fn new(device: &wgpu::Device, indices: &[u32]) -> Self {
+        Self {
+            buffer: create_buffer(
+                device,
+                bytemuck::cast_slice(indices),
+                wgpu::BufferUsages::INDEX,
             ),
-            num_indices: indices
+            num_indices: num_indices(indices),
+        }
+    }
+
+    fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        indices: &[u32],
+    ) {
+        write_buffer(
+            device,
+            queue,
+            &mut self.buffer,
+            bytemuck::cast_slice(indices),
+            wgpu::BufferUsages::INDEX,
+        );
+        self.num_indices = num_indices(indices);
+    }
+}
+
+/// The feature edges of a mesh, one instance per edge
+#[derive(Debug)]
+pub struct FeatureEdges {
+    pub buffer: wgpu::Buffer,
+    pub num_edges: u32,
+}
+
+impl FeatureEdges {
+    fn new(device: &wgpu::Device, edges: &[FeatureEdge]) -> Self {
+        Self {
+            buffer: create_buffer(
+                device,
+                bytemuck::cast_slice(edges),
+                wgpu::BufferUsages::VERTEX,
+            ),
+            num_edges: edges
                 .len()
                 .try_into()
                 .expect("`usize` couldn't be cast to `u32`"),
         }
     }
+
+    fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        edges: &[FeatureEdge],
+    ) {
+        write_buffer(
+            device,
+            queue,
+            &mut self.buffer,
+            bytemuck::cast_slice(edges),
+            wgpu::BufferUsages::VERTEX,
+        );
+        self.num_edges = edges
+            .len()
+            .try_into()
+            .expect("`usize` couldn't be cast to `u32`");
+    }
+}
+
+fn num_indices(indices: &[u32]) -> u32 {
+    indices
+        .len()
+        .try_into()
+        .expect("`usize` couldn't be cast to `u32`")
+}
+
+fn create_buffer(
+    device: &wgpu::Device,
+    contents: &[u8],
+    usage: wgpu::BufferUsages,
+) -> wgpu::Buffer {
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: None,
+        contents,
+        usage: usage | wgpu::BufferUsages::COPY_DST,
+    })
+}
+
+fn write_buffer(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    buffer: &mut wgpu::Buffer,
+    contents: &[u8],
+    usage: wgpu::BufferUsages,
+) {
+    if contents.len() as u64 > buffer.size() {
+        *buffer = create_buffer(device, contents, usage);
+    } else if !contents.is_empty() {
+        queue.write_buffer(buffer, 0, contents);
+    }
 }
@@ -0,0 +1,243 @@
+use crate::fjviewer::Camera;
+
+use super::uniforms::{LightUniforms, LightingUniforms};
+
+/// How the model is lit
+///
+/// The model is shaded with the Blinn-Phong reflection model: An ambient term,
+/// plus a diffuse and a specular term for each light.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lighting {
+    /// The color and intensity of the ambient light
+    pub ambient: [f32; 3],
+
+    /// The lights, of which only the first [`Lighting::MAX_LIGHTS`] are used
+    pub lights: Vec<Light>,
+
+    /// The intensity of specular highlights, relative to the lights
+    pub specular: f32,
+
+    /// The Blinn-Phong exponent; the higher, the smaller the highlights
+    pub shininess: f32,
+
+    /// The intensity of the light from the environment map
+    ///
+    /// The environment is reflected by the model, depending on its
+    /// [`Material`], and lights it diffusely.
+    pub environment: f32,
+}
+
+impl Lighting {
+    /// The number of lights that the shader supports
+    pub const MAX_LIGHTS: usize = 4;
+
+    /// A single light at the camera
+    ///
+    /// Every surface facing the camera is lit, no matter how the view is
+    /// rotated.
+    pub fn headlight() -> Self {
+        Self {
+            ambient: [0.25; 3],
+            lights: vec![Light::point(
+                [0., 0., 0.],
+                [0.75; 3],
+                LightSpace::Camera,
+            )],
+            specular: 0.3,
+            shininess: 32.,
+            environment: 0.,
+        }
+    }
+
+    /// A key, a fill and a back light, fixed around the model
+    ///
+    /// The key light shines from the front left, the weaker fill light from
+    /// the right, and the back light from behind, to bring out the outline of
+    /// the model. Rotating the view shows the model under different angles,
+    /// as if it was placed in a photo studio.
+    pub fn studio() -> Self {
+        Self {
+            ambient: [0.15; 3],
+            lights: vec![
+                Light::directional(
+                    [-1., -2., 2.],
+                    [0.7, 0.68, 0.65],
+                    LightSpace::World,
+                ),
+                Light::directional(
+                    [2., -1., 0.5],
+                    [0.25, 0.27, 0.3],
+                    LightSpace::World,
+                ),
+                Light::directional([0.5, 2., 1.5], [0.4; 3], LightSpace::World),
+            ],
+            specular: 0.4,
+            shininess: 48.,
+            environment: 0.,
+        }
+    }
+
+    /// Only the light from the environment map
+    ///
+    /// Use this with a [`Material`] that is metallic or smooth, to see the
+    /// environment reflected by the model.
+    pub fn environment() -> Self {
+        Self {
+            ambient: [0.; 3],
+            lights: Vec::new(),
+            specular: 0.,
+            shininess: 1.,
+            environment: 1.,
+        }
+    }
+
+    /// Compute the uniforms for the shader, with the lights in camera space
+    pub fn uniforms(
+        &self,
+        camera: &Camera,
+        material: &Material,
+    ) -> LightingUniforms {
+        let model_to_camera = camera.camera_to_model();
+
+        let mut lights = [LightUniforms::default(); Self::MAX_LIGHTS];
+        let mut num_lights = 0;
+
+        for (uniforms, light) in lights.iter_mut().zip(&self.lights) {
+            let vector = match (light.space, light.kind) {
+                (LightSpace::Camera, _) => light.vector,
+                (LightSpace::World, LightKind::Directional) => model_to_camera
+                    .transform_vector(&fj_math::Vector::from(light.vector))
+                    .components
+                    .map(|value| value.into_f64()),
+                (LightSpace::World, LightKind::Point) => model_to_camera
+                    .transform_point(&fj_math::Point::from(light.vector))
+                    .coords
+                    .components
+                    .map(|value| value.into_f64()),
+            };
+            let [x, y, z] = vector.map(|value| value as f32);
+            let w = match light.kind {
+                LightKind::Directional => 0.,
+                LightKind::Point => 1.,
+            };
+
+            *uniforms = LightUniforms {
+                vector: [x, y, z, w],
+                color: [light.color[0], light.color[1], light.color[2], 0.],
+            };
+            num_lights += 1;
+        }
+
+        LightingUniforms {
+            ambient: [self.ambient[0], self.ambient[1], self.ambient[2], 0.],
+            lights,
+            num_lights,
+            specular: self.specular,
+            shininess: self.shininess,
+            environment: self.environment,
+            metallic: material.metallic,
+            roughness: material.roughness,
+            _padding: [0.; 2],
+        }
+    }
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Self::headlight()
+    }
+}
+
+/// A light that illuminates the model
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Light {
+    kind: LightKind,
+
+    /// The direction towards the light, or the position of the light
+    vector: [f64; 3],
+
+    /// The color and intensity of the light
+    color: [f32; 3],
+
+    space: LightSpace,
+}
+
+impl Light {
+    /// A light infinitely far away, shining from `direction`
+    pub fn directional(
+        direction: [f64; 3],
+        color: [f32; 3],
+        space: LightSpace,
+    ) -> Self {
+        Self {
+            kind: LightKind::Directional,
+            vector: direction,
+            color,
+            space,
+        }
+    }
+
+    /// A light at `position`, shining in all directions
+    ///
+    /// The light doesn't fall off with distance, so it works the same for
+    /// models of any size.
+    pub fn point(
+        position: [f64; 3],
+        color: [f32; 3],
+        space: LightSpace,
+    ) -> Self {
+        Self {
+            kind: LightKind::Point,
+            vector: position,
+            color,
+            space,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum LightKind {
+    Directional,
+    Point,
+}
+
+/// The space that the direction or position of a [`Light`] is given in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LightSpace {
+    /// Relative to the camera, with x pointing right, y up, and z towards the
+    /// viewer
+    ///
+    /// The light moves along with the camera.
+    Camera,
+
+    /// In model coordinates
+    ///
+    /// The light stays where it is, as the camera moves around the model.
+    World,
+}
+
+/// How the surface of the model reflects light from the environment
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Material {
+    /// How metallic the surface is, from 0 to 1
+    ///
+    /// Metals reflect the environment tinted with their color, and have no
+    /// diffuse reflection. Other materials reflect a little of the
+    /// environment at steep angles, and more at grazing ones.
+    pub metallic: f32,
+
+    /// How rough the surface is, from 0 to 1
+    ///
+    /// Smooth surfaces reflect the environment like a mirror. The rougher the
+    /// surface, the blurrier the reflections.
+    pub roughness: f32,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            metallic: 0.,
+            roughness: 0.5,
+        }
+    }
+}
@@ -1,14 +1,23 @@
 //! Rendering primitives, routines, and structures.
 
+pub mod blit;
 mod draw_config;
 pub mod drawables;
+pub mod environment;
 pub mod geometries;
+mod lighting;
+// The navigation cube and the textured models it is made of aren't drawn by
+// the shader widget yet.
+#[allow(dead_code)]
 mod model;
+#[allow(dead_code)]
 pub mod navigation_cube;
 pub mod pipelines;
 // mod renderer;
 mod shaders;
-mod texture;
+pub mod skybox;
+#[allow(dead_code)]
+pub mod texture;
 pub mod transform;
 pub mod uniforms;
 pub mod vertices;
@@ -17,9 +26,91 @@ use iced_wgpu::wgpu;
 
 pub use self::{
     // device::DeviceError,
-    draw_config::DrawConfig,
+    draw_config::{Background, DrawConfig},
+    lighting::{Light, LightSpace, Lighting, Material},
     // renderer::{Renderer, RendererInitError},
 };
 
 pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
-pub const SAMPLE_COUNT: u32 = 4;
+
+/// The number of samples per pixel, unless configured otherwise
+pub const DEFAULT_SAMPLE_COUNT: u32 = 4;
+
+/// Returns the supported number of samples per pixel that is closest to
+/// `requested`
+///
+/// `color` and `depth` are the features of the color format and of
+/// [`DEPTH_FORMAT`], that textures are validated against. Where the adapter is
+/// at hand, these come from [`wgpu::Adapter::get_texture_format_features`].
+///
+/// Unsupported counts are rounded up to the next supported one, so asking for
+/// anti-aliasing never turns it off. If there is no higher supported count,
+/// the highest one is used.
+pub fn supported_sample_count(
+    requested: u32,
+    color: &wgpu::TextureFormatFeatures,
+    depth: &wgpu::TextureFormatFeatures,
+) -> u32 {
+    let is_supported = |count: u32| {
+        count == 1
+            || (color.flags.sample_count_supported(count)
+                && color.flags.contains(
+                    wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE,
+                )
+                && depth.flags.sample_count_supported(count))
+    };
+
+    let mut supported = [1, 2, 4, 8]
+        .into_iter()
+        .filter(|&count| is_supported(count));
+    let highest = supported.clone().next_back().unwrap_or(1);
+
+    supported
+        .find(|&count| count >= requested)
+        .unwrap_or(highest)
+}
+
+#[cfg(test)]
+mod tests {
+    use iced_wgpu::wgpu;
+    use wgpu::TextureFormatFeatureFlags as Flags;
+
+    use super::{supported_sample_count, DEPTH_FORMAT};
+
+    #[test]
+    fn supported_sample_counts_are_kept() {
+        let features = |flags| wgpu::TextureFormatFeatures {
+            allowed_usages: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            flags,
+        };
+        let color = features(
+            Flags::MULTISAMPLE_X2
+                | Flags::MULTISAMPLE_X4
+                | Flags::MULTISAMPLE_X8
+                | Flags::MULTISAMPLE_RESOLVE,
+        );
+        let depth = features(
+            Flags::MULTISAMPLE_X2
+                | Flags::MULTISAMPLE_X4
+                | Flags::MULTISAMPLE_X8,
+        );
+
+        let supported = [1, 2, 4, 8]
+            .map(|requested| supported_sample_count(requested, &color, &depth));
+
+        assert_eq!(supported, [1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn unsupported_sample_counts_are_rounded_up() {
+        let features = wgpu::Features::empty();
+        let color = wgpu::TextureFormat::Bgra8UnormSrgb
+            .guaranteed_format_features(features);
+        let depth = DEPTH_FORMAT.guaranteed_format_features(features);
+
+        let supported = [1, 2, 4, 8]
+            .map(|requested| supported_sample_count(requested, &color, &depth));
+
+        assert_eq!(supported, [1, 4, 4, 4]);
+    }
+}
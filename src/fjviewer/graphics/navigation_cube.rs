@@ -2,6 +2,8 @@ use fj_math::Transform;
 use iced_wgpu::wgpu;
 use wgpu::util::DeviceExt;
 
+use crate::fjviewer::to_nalgebra;
+
 use super::{
     model::{self, load_model, DrawModel, Model},
     transform,
@@ -214,9 +216,7 @@ impl NavigationCubeRenderer {
             0.0, 0.0, 0.0, 1.0,
         );
         
-        // For simplicity, use identity rotation for now
-        // TODO: Convert fj_math::Transform rotation to nalgebra format
-        let rotation_matrix = nalgebra::Matrix4::identity();
+        let rotation_matrix = to_nalgebra(&rotation);
         
         let scale_matrix = nalgebra::Matrix4::new(
             SCALE_FACTOR, 0.0, 0.0, 0.0,
@@ -234,7 +234,7 @@ impl NavigationCubeRenderer {
         
         let model_transform = translation_matrix * rotation_matrix * scale_matrix;
         
-        let proj_mat = perspective.to_projective().matrix().clone();
+        let proj_mat = *perspective.to_projective().matrix();
         
         let matrix = screen_transform * proj_mat * view_matrix * model_transform;
 
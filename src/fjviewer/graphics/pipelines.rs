@@ -2,14 +2,15 @@ use std::mem::size_of;
 use iced_wgpu::wgpu;
 use super::{
     shaders::{Shader, Shaders},
-    vertices::Vertex,
-    DEPTH_FORMAT, SAMPLE_COUNT,
+    vertices::{FeatureEdge, Vertex},
+    DEPTH_FORMAT,
 };
 
 #[derive(Debug)]
 pub struct Pipelines {
     pub model: Pipeline,
-    pub mesh: Option<Pipeline>,
+    pub mesh: Pipeline,
+    pub feature_edges: Pipeline,
 }
 
 impl Pipelines {
@@ -17,7 +18,7 @@ impl Pipelines {
         device: &wgpu::Device,
         bind_group_layout: &wgpu::BindGroupLayout,
         color_format: wgpu::TextureFormat,
-        features: wgpu::Features,
+        sample_count: u32,
     ) -> Self {
         let pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -32,30 +33,55 @@ impl Pipelines {
             device,
             &pipeline_layout,
             shaders.model(),
-            wgpu::PrimitiveTopology::TriangleList,
-            wgpu::PolygonMode::Fill,
+            Primitives::Triangles,
             color_format,
+            sample_count,
         );
 
-        let mesh = if features.contains(wgpu::Features::POLYGON_MODE_LINE) {
-            // We need this feature, otherwise initializing the pipeline will
-            // panic.
-            Some(Pipeline::new(
-                device,
-                &pipeline_layout,
-                shaders.mesh(),
-                wgpu::PrimitiveTopology::TriangleList,
-                wgpu::PolygonMode::Line,
-                color_format,
-            ))
-        } else {
-            None
-        };
-        
-        Self { model, mesh }
+        // The wireframe is drawn from a line list of the triangle edges, rather
+        // than with `wgpu::PolygonMode::Line`, which isn't supported
+        // everywhere.
+        let mesh = Pipeline::new(
+            device,
+            &pipeline_layout,
+            shaders.mesh(),
+            Primitives::Lines,
+            color_format,
+            sample_count,
+        );
+
+        let feature_edges = Pipeline::new(
+            device,
+            &pipeline_layout,
+            shaders.feature_edges(),
+            Primitives::FeatureEdges,
+            color_format,
+            sample_count,
+        );
+
+        Self {
+            model,
+            mesh,
+            feature_edges,
+        }
     }
 }
 
+/// What a [`Pipeline`] draws
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Primitives {
+    /// A triangle list, indexing into the vertices
+    Triangles,
+
+    /// A line list, indexing into the vertices
+    Lines,
+
+    /// A quad per [`FeatureEdge`] instance, expanded in the vertex shader
+    ///
+    /// The quads are blended on top of the model, so they don't write depth.
+    FeatureEdges,
+}
+
 #[derive(Debug)]
 pub struct Pipeline(pub wgpu::RenderPipeline);
 
@@ -64,18 +90,21 @@ impl Pipeline {
         device: &wgpu::Device,
         pipeline_layout: &wgpu::PipelineLayout,
         shader: Shader,
-        topology: wgpu::PrimitiveTopology,
-        polygon_mode: wgpu::PolygonMode,
+        primitives: Primitives,
         color_format: wgpu::TextureFormat,
+        sample_count: u32,
     ) -> Self {
-        let pipeline =
-            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: None,
-                layout: Some(pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: shader.module,
-                    entry_point: "vertex",
-                    buffers: &[wgpu::VertexBufferLayout {
+        let (topology, vertex_buffer) = match primitives {
+            Primitives::Triangles | Primitives::Lines => {
+                let topology = if primitives == Primitives::Triangles {
+                    wgpu::PrimitiveTopology::TriangleList
+                } else {
+                    wgpu::PrimitiveTopology::LineList
+                };
+
+                (
+                    topology,
+                    wgpu::VertexBufferLayout {
                         array_stride: size_of::<Vertex>() as u64,
                         step_mode: wgpu::VertexStepMode::Vertex,
                         attributes: &wgpu::vertex_attr_array![
@@ -83,7 +112,33 @@ impl Pipeline {
                             1 => Float32x3,
                             2 => Float32x4,
                         ],
-                    }],
+                    },
+                )
+            }
+            Primitives::FeatureEdges => (
+                wgpu::PrimitiveTopology::TriangleList,
+                wgpu::VertexBufferLayout {
+                    array_stride: size_of::<FeatureEdge>() as u64,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![
+                        0 => Float32x3,
+                        1 => Float32x3,
+                        2 => Float32x3,
+                        3 => Float32x3,
+                        4 => Uint32,
+                    ],
+                },
+            ),
+        };
+
+        let pipeline =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: shader.module,
+                    entry_point: shader.vertex_entry,
+                    buffers: &[vertex_buffer],
                 },
                 primitive: wgpu::PrimitiveState {
                     topology,
@@ -91,13 +146,14 @@ impl Pipeline {
                     front_face: wgpu::FrontFace::Ccw,
                     cull_mode: None,
                     unclipped_depth: false,
-                    polygon_mode,
+                    polygon_mode: wgpu::PolygonMode::Fill,
                     conservative: false,
                 },
                 depth_stencil: Some(wgpu::DepthStencilState {
                     format: DEPTH_FORMAT,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::LessEqual,
+                    depth_write_enabled: primitives != Primitives::FeatureEdges,
+                    // Reverse-Z: Closer fragments have greater depth values.
+                    depth_compare: wgpu::CompareFunction::GreaterEqual,
                     stencil: wgpu::StencilState {
                         front: wgpu::StencilFaceState::IGNORE,
                         back: wgpu::StencilFaceState::IGNORE,
@@ -107,9 +163,10 @@ impl Pipeline {
                     bias: wgpu::DepthBiasState::default(),
                 }),
                 multisample: wgpu::MultisampleState {
-                    count: SAMPLE_COUNT,
+                    count: sample_count,
                     mask: !0,
-                    alpha_to_coverage_enabled: true,
+                    // Alpha to coverage needs more than one sample to work.
+                    alpha_to_coverage_enabled: sample_count > 1,
                 },
                 fragment: Some(wgpu::FragmentState {
                     module: shader.module,
@@ -0,0 +1,127 @@
+// Convolves an environment map, for image-based lighting
+
+struct Uniforms {
+    // The face of the cube map that is rendered, in the order +x, -x, +y, -y,
+    // +z, -z
+    face: u32,
+    mode: u32,
+    roughness: f32,
+    _padding: f32,
+};
+
+// Glossy reflections, for the given roughness
+const mode_specular: u32 = 0u;
+
+// Diffuse irradiance
+const mode_irradiance: u32 = 1u;
+
+const pi: f32 = 3.14159265359;
+const sample_count: u32 = 512u;
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@group(0) @binding(1)
+var source: texture_cube<f32>;
+
+@group(0) @binding(2)
+var source_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) screen: vec2<f32>,
+};
+
+@vertex
+fn vertex(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    out.screen = fullscreen_corner(index);
+    out.position = vec4<f32>(out.screen, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let normal = normalize(face_direction(uniforms.face, in.screen));
+
+    // An orthonormal basis around the normal
+    var up = vec3<f32>(0.0, 1.0, 0.0);
+    if abs(normal.y) > 0.999 {
+        up = vec3<f32>(1.0, 0.0, 0.0);
+    }
+    let tangent = normalize(cross(up, normal));
+    let bitangent = cross(normal, tangent);
+
+    var color = vec3<f32>(0.0);
+    var weight = 0.0;
+
+    for (var i = 0u; i < sample_count; i++) {
+        let xi = hammersley(i);
+
+        if uniforms.mode == mode_irradiance {
+            // Cosine-weighted samples of the hemisphere. The cosine term of
+            // the irradiance cancels out against their distribution.
+            let phi = 2.0 * pi * xi.x;
+            let sin_theta = sqrt(xi.y);
+            let local = vec3<f32>(
+                cos(phi) * sin_theta,
+                sin(phi) * sin_theta,
+                sqrt(1.0 - xi.y),
+            );
+            let direction = tangent * local.x + bitangent * local.y
+                + normal * local.z;
+
+            color += textureSampleLevel(source, source_sampler, direction, 0.0)
+                .rgb;
+            weight += 1.0;
+        } else {
+            // Samples of the GGX distribution, assuming that the direction
+            // towards the viewer is the same as the normal.
+            let a = uniforms.roughness * uniforms.roughness;
+            let phi = 2.0 * pi * xi.x;
+            let cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+            let sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+            let halfway = tangent * cos(phi) * sin_theta
+                + bitangent * sin(phi) * sin_theta
+                + normal * cos_theta;
+            let direction = reflect(-normal, halfway);
+
+            let n_dot_l = dot(normal, direction);
+            if n_dot_l > 0.0 {
+                color += textureSampleLevel(
+                    source,
+                    source_sampler,
+                    direction,
+                    0.0,
+                ).rgb * n_dot_l;
+                weight += n_dot_l;
+            }
+        }
+    }
+
+    return vec4<f32>(color / max(weight, 1e-4), 1.0);
+}
+
+// The direction from the center of the cube through a point on one of its
+// faces, given in normalized device coordinates of the face
+fn face_direction(face: u32, screen: vec2<f32>) -> vec3<f32> {
+    let x = screen.x;
+    let y = screen.y;
+
+    switch face {
+        case 0u: { return vec3<f32>(1.0, y, -x); }
+        case 1u: { return vec3<f32>(-1.0, y, x); }
+        case 2u: { return vec3<f32>(x, 1.0, -y); }
+        case 3u: { return vec3<f32>(x, -1.0, y); }
+        case 4u: { return vec3<f32>(x, y, 1.0); }
+        default: { return vec3<f32>(-x, y, -1.0); }
+    }
+}
+
+// A low-discrepancy sequence of points in the unit square
+fn hammersley(i: u32) -> vec2<f32> {
+    return vec2<f32>(
+        f32(i) / f32(sample_count),
+        f32(reverseBits(i)) * 2.3283064365386963e-10,
+    );
+}
@@ -1,11 +1,62 @@
+struct Light {
+    // The direction towards the light, with a `w` of 0, or its position, with
+    // a `w` of 1. Either is in camera space.
+    vector: vec4<f32>,
+    color: vec4<f32>,
+}
+
+const max_lights: u32 = 4u;
+
+struct Lighting {
+    ambient: vec4<f32>,
+    lights: array<Light, max_lights>,
+    num_lights: u32,
+    specular: f32,
+    shininess: f32,
+
+    // The intensity of the light from the environment map
+    environment: f32,
+
+    // The material of the model
+    metallic: f32,
+    roughness: f32,
+}
+
 struct Uniforms {
     transform: mat4x4<f32>,
     transform_normals: mat4x4<f32>,
+    transform_view: mat4x4<f32>,
+
+    // The position of the camera in model space, with a `w` of 1, or the
+    // direction towards it, with a `w` of 0, for orthographic projection.
+    eye: vec4<f32>,
+    viewport_size: vec2<f32>,
+    edge_width: f32,
+
+    // The cosine of the crease angle
+    crease_cos: f32,
+
+    lighting: Lighting,
 };
 
 @group(0) @binding(0)
 var<uniform> uniforms: Uniforms;
 
+// The environment map, convolved for glossy reflections, with rougher surfaces
+// in higher mip levels
+@group(0) @binding(1)
+var environment_specular: texture_cube<f32>;
+
+// The environment map, convolved for diffuse lighting
+@group(0) @binding(2)
+var environment_irradiance: texture_cube<f32>;
+
+@group(0) @binding(3)
+var environment_sampler: sampler;
+
+// Matches `Environment::SPECULAR_MIP_LEVELS`.
+const environment_specular_mip_levels: f32 = 5.0;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
@@ -16,6 +67,14 @@ struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) normal: vec3<f32>,
     @location(1) color: vec4<f32>,
+
+    // The position in camera space
+    @location(2) view_position: vec3<f32>,
+
+    // The position and the normal in model space, for sampling the
+    // environment, which is fixed to the model
+    @location(3) model_position: vec3<f32>,
+    @location(4) model_normal: vec3<f32>,
 };
 
 struct FragmentOutput {
@@ -24,35 +83,266 @@ struct FragmentOutput {
 
 @vertex
 fn vertex(in: VertexInput) -> VertexOutput {
+    return transform_vertex(in);
+}
+
+fn transform_vertex(in: VertexInput) -> VertexOutput {
     var out: VertexOutput;
     out.normal = (uniforms.transform_normals * vec4<f32>(in.normal, 0.0)).xyz;
     out.position = uniforms.transform * vec4<f32>(in.position, 1.0);
+    out.view_position =
+        (uniforms.transform_view * vec4<f32>(in.position, 1.0)).xyz;
+    out.model_position = in.position;
+    out.model_normal = in.normal;
     // We use premultiplied alpha blending.
     out.color = vec4<f32>(in.color.rgb * in.color.a, in.color.a);
 
     return out;
 }
 
-const pi: f32 = 3.14159265359;
+// How far edges are moved towards the camera, relative to their depth.
+//
+// This keeps them from being hidden by the triangles they belong to. The depth
+// bias of the pipeline can't be used for that, as it only applies to
+// triangles.
+const edge_depth_bias: f32 = 1e-4;
+
+@vertex
+fn vertex_edges(in: VertexInput) -> VertexOutput {
+    var out = transform_vertex(in);
+
+    // Reverse-Z: Closer fragments have greater depth values.
+    out.position.z *= 1.0 + edge_depth_bias;
+
+    return out;
+}
+
+struct FeatureEdgeInput {
+    @location(0) a: vec3<f32>,
+    @location(1) b: vec3<f32>,
+    @location(2) normal_a: vec3<f32>,
+    @location(3) normal_b: vec3<f32>,
+    @location(4) kind: u32,
+}
+
+struct FeatureEdgeOutput {
+    @builtin(position) position: vec4<f32>,
+
+    // The distance from the center of the line, in pixels
+    @location(0) distance: f32,
+}
+
+const feature_edge_shared: u32 = 0u;
+
+// Feature edges are drawn as a quad of two triangles per instance, stretched
+// along the edge in screen space, so they have the same width everywhere.
+@vertex
+fn vertex_feature_edges(
+    @builtin(vertex_index) vertex_index: u32,
+    in: FeatureEdgeInput,
+) -> FeatureEdgeOutput {
+    var out: FeatureEdgeOutput;
+
+    // Vertices outside of the view volume. The triangles they form have no
+    // area and aren't rasterized.
+    let culled = vec4<f32>(2.0, 2.0, 2.0, 1.0);
+    out.position = culled;
+
+    if in.kind == feature_edge_shared {
+        let view = uniforms.eye.xyz - (in.a + in.b) * 0.5 * uniforms.eye.w;
 
+        let is_crease = dot(in.normal_a, in.normal_b) < uniforms.crease_cos;
+        let is_silhouette =
+            dot(in.normal_a, view) * dot(in.normal_b, view) < 0.0;
+
+        if !is_crease && !is_silhouette {
+            return out;
+        }
+    }
+
+    var a = uniforms.transform * vec4<f32>(in.a, 1.0);
+    var b = uniforms.transform * vec4<f32>(in.b, 1.0);
+
+    // Clip the edge against the near and far planes. Otherwise the division
+    // by `w` below would mirror anything behind the camera. Reverse-Z: The
+    // near plane is at a depth of 1, the far plane at 0.
+    let near_a = a.w - a.z;
+    let near_b = b.w - b.z;
+    if (near_a < 0.0 && near_b < 0.0) || (a.z < 0.0 && b.z < 0.0) {
+        return out;
+    }
+    if near_a < 0.0 {
+        a = mix(a, b, near_a / (near_a - near_b));
+    } else if near_b < 0.0 {
+        b = mix(b, a, near_b / (near_b - near_a));
+    }
+    if a.z < 0.0 {
+        a = mix(a, b, a.z / (a.z - b.z));
+    } else if b.z < 0.0 {
+        b = mix(b, a, b.z / (b.z - a.z));
+    }
+
+    let half_size = uniforms.viewport_size * 0.5;
+    let screen_a = a.xy / a.w * half_size;
+    let screen_b = b.xy / b.w * half_size;
+
+    var direction = vec2<f32>(1.0, 0.0);
+    if distance(screen_a, screen_b) > 0.0 {
+        direction = normalize(screen_b - screen_a);
+    }
+    let normal = vec2<f32>(-direction.y, direction.x);
+
+    // Leave room for a pixel of anti-aliasing on each side.
+    let extent = uniforms.edge_width * 0.5 + 1.0;
+
+    var ends = array<f32, 6>(0.0, 1.0, 1.0, 0.0, 1.0, 0.0);
+    var sides = array<f32, 6>(-1.0, -1.0, 1.0, -1.0, 1.0, 1.0);
+    let end = ends[vertex_index];
+    let side = sides[vertex_index];
+
+    // The quad extends past the ends of the edge, so edges meeting at an
+    // angle have no gaps between them.
+    let offset = (normal * side + direction * (end * 2.0 - 1.0)) * extent;
+
+    var position = mix(a, b, end);
+    position = vec4<f32>(
+        position.xy + offset / half_size * position.w,
+        position.z * (1.0 + edge_depth_bias),
+        position.w,
+    );
+
+    out.position = position;
+    out.distance = side * extent;
+
+    return out;
+}
+
+// Blinn-Phong shading in camera space, plus image-based lighting in model
+// space
 @fragment
 fn frag_model(in: VertexOutput) -> FragmentOutput {
-    let light = vec3<f32>(0.0, 0.0, -1.0);
+    // The direction towards the viewer. With orthographic projection, that is
+    // the same everywhere.
+    var to_viewer = vec3<f32>(0.0, 0.0, 1.0);
+    if uniforms.eye.w != 0.0 {
+        to_viewer = normalize(-in.view_position);
+    }
+
+    // Light both sides of the triangles, so models that are open, or whose
+    // triangles face the wrong way, still look right.
+    var normal = normalize(in.normal);
+    if dot(normal, to_viewer) < 0.0 {
+        normal = -normal;
+    }
+
+    var diffuse = uniforms.lighting.ambient.rgb;
+    var specular = vec3<f32>(0.0);
+
+    let num_lights = min(uniforms.lighting.num_lights, max_lights);
+    for (var i = 0u; i < num_lights; i++) {
+        let light = uniforms.lighting.lights[i];
+        let to_light = normalize(
+            light.vector.xyz - in.view_position * light.vector.w
+        );
 
-    let angle = acos(dot(light, -in.normal));
-    let f_angle = angle / (pi * 0.75);
+        let n_dot_l = dot(normal, to_light);
+        if n_dot_l <= 0.0 {
+            continue;
+        }
 
-    let f_normal = max(1.0 - f_angle, 0.0);
+        let halfway = normalize(to_light + to_viewer);
+        diffuse += light.color.rgb * n_dot_l;
+        let shininess = uniforms.lighting.shininess;
+        specular +=
+            light.color.rgb * pow(max(dot(normal, halfway), 0.0), shininess);
+    }
+
+    // Metals have no diffuse reflection, and tint their specular reflections.
+    let metallic = uniforms.lighting.metallic;
+    let alpha = in.color.a;
+    let color = in.color.rgb / max(alpha, 1e-4);
+    let diffuse_color = color * (1.0 - metallic);
+    let specular_color = mix(vec3<f32>(0.04), color, metallic);
+
+    let highlight = specular * uniforms.lighting.specular
+        * mix(vec3<f32>(1.0), color, metallic);
+    var shaded = diffuse_color * diffuse + highlight;
+
+    if uniforms.lighting.environment > 0.0 {
+        shaded += image_based_lighting(in, diffuse_color, specular_color)
+            * uniforms.lighting.environment;
+    }
 
     var out: FragmentOutput;
-    out.color = vec4<f32>(in.color.rgb * f_normal, in.color.a);
+    // We use premultiplied alpha blending, so the reflections fade along with
+    // transparent surfaces.
+    out.color = vec4<f32>(shaded * alpha, alpha);
 
     return out;
 }
 
+fn image_based_lighting(
+    in: VertexOutput,
+    diffuse_color: vec3<f32>,
+    specular_color: vec3<f32>,
+) -> vec3<f32> {
+    let roughness = uniforms.lighting.roughness;
+
+    let to_viewer = normalize(
+        uniforms.eye.xyz - in.model_position * uniforms.eye.w
+    );
+    var normal = normalize(in.model_normal);
+    if dot(normal, to_viewer) < 0.0 {
+        normal = -normal;
+    }
+    let n_dot_v = max(dot(normal, to_viewer), 0.0);
+
+    // Schlick's approximation, with the reflections of rough surfaces
+    // dimmed towards grazing angles
+    let fresnel = specular_color
+        + (max(vec3<f32>(1.0 - roughness), specular_color) - specular_color)
+        * pow(1.0 - n_dot_v, 5.0);
+
+    let irradiance = sample_environment(
+        environment_irradiance,
+        normal,
+        0.0,
+    );
+    let reflection = sample_environment(
+        environment_specular,
+        reflect(-to_viewer, normal),
+        roughness * (environment_specular_mip_levels - 1.0),
+    );
+
+    return diffuse_color * irradiance * (1.0 - fresnel) + reflection * fresnel;
+}
+
+fn sample_environment(
+    environment: texture_cube<f32>,
+    direction: vec3<f32>,
+    level: f32,
+) -> vec3<f32> {
+    return textureSampleLevel(
+        environment,
+        environment_sampler,
+        cube_direction(direction),
+        level,
+    ).rgb;
+}
+
 @fragment
 fn frag_mesh(in: VertexOutput) -> FragmentOutput {
     var out: FragmentOutput;
     out.color = vec4<f32>(1.0 - in.color.rgb, in.color.a);
     return out;
 }
+
+@fragment
+fn frag_feature_edges(in: FeatureEdgeOutput) -> FragmentOutput {
+    let coverage =
+        clamp(uniforms.edge_width * 0.5 + 0.5 - abs(in.distance), 0.0, 1.0);
+
+    var out: FragmentOutput;
+    out.color = vec4<f32>(0.0, 0.0, 0.0, coverage);
+    return out;
+}
@@ -7,31 +7,43 @@ impl Shaders {
         let module =
             device.create_shader_module(wgpu::ShaderModuleDescriptor {
                 label: None,
-                source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!(
-                    "shader.wgsl"
+                source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(concat!(
+                    include_str!("environment.wgsl"),
+                    include_str!("shader.wgsl"),
                 ))),
             });
 
         Self(module)
     }
 
-    pub fn model(&self) -> Shader {
+    pub fn model(&self) -> Shader<'_> {
         Shader {
             module: &self.0,
+            vertex_entry: "vertex",
             frag_entry: "frag_model",
         }
     }
 
-    pub fn mesh(&self) -> Shader {
+    pub fn mesh(&self) -> Shader<'_> {
         Shader {
             module: &self.0,
+            vertex_entry: "vertex_edges",
             frag_entry: "frag_mesh",
         }
     }
+
+    pub fn feature_edges(&self) -> Shader<'_> {
+        Shader {
+            module: &self.0,
+            vertex_entry: "vertex_feature_edges",
+            frag_entry: "frag_feature_edges",
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
 pub struct Shader<'r> {
     pub module: &'r wgpu::ShaderModule,
+    pub vertex_entry: &'static str,
     pub frag_entry: &'static str,
 }
@@ -0,0 +1,241 @@
+//! Drawing the background behind the model
+
+use std::borrow::Cow;
+
+use bytemuck::{Pod, Zeroable};
+use iced_wgpu::wgpu;
+use wgpu::util::DeviceExt;
+
+use crate::fjviewer::Camera;
+
+use super::{texture::CubeTexture, Background, DEPTH_FORMAT};
+
+/// Draws a gradient or an environment map behind the model
+///
+/// Solid backgrounds don't need a pass of their own. The render target is
+/// cleared to their color instead.
+#[derive(Debug)]
+pub struct Skybox {
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Skybox {
+    pub fn new(
+        device: &wgpu::Device,
+        color_format: wgpu::TextureFormat,
+        sample_count: u32,
+        environment: &CubeTexture,
+    ) -> Self {
+        let module =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(concat!(
+                    include_str!("fullscreen.wgsl"),
+                    include_str!("environment.wgsl"),
+                    include_str!("skybox.wgsl"),
+                ))),
+            });
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float {
+                                filterable: true,
+                            },
+                            view_dimension: wgpu::TextureViewDimension::Cube,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(
+                            wgpu::SamplerBindingType::Filtering,
+                        ),
+                        count: None,
+                    },
+                ],
+            });
+
+        let uniform_buffer =
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&[SkyboxUniforms::zeroed()]),
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST,
+            });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(
+                        &environment.view,
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(
+                        &environment.sampler,
+                    ),
+                },
+            ],
+        });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let pipeline =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: "vertex",
+                    buffers: &[],
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                // The background is drawn first, so it doesn't need to be
+                // depth tested. It still has to match the render pass.
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: DEPTH_FORMAT,
+                    depth_write_enabled: false,
+                    depth_compare: wgpu::CompareFunction::Always,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    ..Default::default()
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: "fragment",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: color_format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview: None,
+            });
+
+        Self {
+            pipeline,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    pub fn update(&self, queue: &wgpu::Queue, uniforms: &SkyboxUniforms) {
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[*uniforms]),
+        );
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+/// The parameters of the [`Skybox`] pass
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct SkyboxUniforms {
+    ray: [f32; 16],
+    top: [f32; 4],
+    bottom: [f32; 4],
+    mode: u32,
+    _padding: [u32; 3],
+}
+
+impl SkyboxUniforms {
+    const MODE_GRADIENT: u32 = 0;
+    const MODE_ENVIRONMENT: u32 = 1;
+
+    /// Compute the uniforms for drawing `background` behind the view of
+    /// `camera`
+    ///
+    /// The environment map only turns along with the camera. It is infinitely
+    /// far away, so moving the camera has no effect on it. With orthographic
+    /// projection, it is shown as if the projection was perspective.
+    pub fn new(
+        camera: &Camera,
+        aspect_ratio: f64,
+        background: &Background,
+    ) -> Self {
+        let (top, bottom, mode) = match *background {
+            Background::Solid(color) => (color, color, Self::MODE_GRADIENT),
+            Background::Gradient { top, bottom } => {
+                (top, bottom, Self::MODE_GRADIENT)
+            }
+            Background::Environment => {
+                ([0.; 3], [0.; 3], Self::MODE_ENVIRONMENT)
+            }
+        };
+
+        // The axes of the camera, in model space, scaled to the extent of the
+        // view at a distance of 1.
+        let camera_to_model = camera.camera_to_model().extract_rotation();
+        let camera_to_model = camera_to_model.inverse();
+        let half_height =
+            (camera.field_of_view().vertical(aspect_ratio) / 2.).tan();
+        let axis = |axis: [f64; 3], scale: f64| {
+            let [x, y, z] = camera_to_model
+                .transform_vector(&fj_math::Vector::from(axis))
+                .components
+                .map(|value| (value.into_f64() * scale) as f32);
+            [x, y, z, 0.]
+        };
+
+        let columns = [
+            axis([1., 0., 0.], half_height * aspect_ratio),
+            axis([0., 1., 0.], half_height),
+            axis([0., 0., 1.], 1.),
+            [0.; 4],
+        ];
+
+        let [tr, tg, tb] = top;
+        let [br, bg, bb] = bottom;
+
+        Self {
+            ray: bytemuck::cast(columns),
+            top: [tr, tg, tb, 1.],
+            bottom: [br, bg, bb, 1.],
+            mode,
+            _padding: [0; 3],
+        }
+    }
+}
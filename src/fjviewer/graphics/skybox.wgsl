@@ -0,0 +1,46 @@
+struct Uniforms {
+    // Maps a point on the screen, in normalized device coordinates, to the
+    // direction of the view ray through it, in model space
+    ray: mat4x4<f32>,
+    top: vec4<f32>,
+    bottom: vec4<f32>,
+    mode: u32,
+};
+
+const mode_gradient: u32 = 0u;
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@group(0) @binding(1)
+var environment: texture_cube<f32>;
+
+@group(0) @binding(2)
+var environment_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) screen: vec2<f32>,
+};
+
+@vertex
+fn vertex(@builtin(vertex_index) index: u32) -> VertexOutput {
+    // Infinitely far away. Reverse-Z: That is a depth of 0.
+    var out: VertexOutput;
+    out.screen = fullscreen_corner(index);
+    out.position = vec4<f32>(out.screen, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    if uniforms.mode == mode_gradient {
+        let t = in.screen.y * 0.5 + 0.5;
+        return vec4<f32>(mix(uniforms.bottom.rgb, uniforms.top.rgb, t), 1.0);
+    }
+
+    let ray = (uniforms.ray * vec4<f32>(in.screen, -1.0, 0.0)).xyz;
+    let color =
+        textureSample(environment, environment_sampler, cube_direction(ray));
+    return vec4<f32>(color.rgb, 1.0);
+}
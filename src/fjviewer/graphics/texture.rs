@@ -12,6 +12,9 @@ pub struct Texture {
 pub enum LoadTextureError {
     #[error("Image processing error")]
     ImageError(#[from] ImageError),
+
+    #[error("Cube map faces must be six square images of the same size")]
+    InvalidCubeMap,
 }
 
 impl Texture {
@@ -85,3 +88,176 @@ impl Texture {
         }
     }
 }
+
+/// A cube map, made of six square faces of the same size
+#[derive(Debug)]
+pub struct CubeTexture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+}
+
+impl CubeTexture {
+    /// The format of cube maps created by [`CubeTexture::render_target`]
+    pub const RENDER_TARGET_FORMAT: wgpu::TextureFormat =
+        wgpu::TextureFormat::Rgba16Float;
+
+    /// Load a cube map from six encoded images
+    ///
+    /// The faces are given in the order that wgpu expects them in: positive x,
+    /// negative x, positive y, negative y, positive z, negative z.
+    pub fn from_bytes(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        faces: [&[u8]; 6],
+        label: &str,
+    ) -> Result<Self, LoadTextureError> {
+        let mut images = Vec::with_capacity(faces.len());
+        for bytes in faces {
+            images.push(image::load_from_memory(bytes)?);
+        }
+
+        Self::from_images(device, queue, &images, Some(label))
+    }
+
+    pub fn from_images(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        faces: &[image::DynamicImage],
+        label: Option<&str>,
+    ) -> Result<Self, LoadTextureError> {
+        let (width, height) = faces
+            .first()
+            .map(|face| face.dimensions())
+            .unwrap_or_default();
+        if faces.len() != 6
+            || width != height
+            || faces
+                .iter()
+                .any(|face| face.dimensions() != (width, height))
+        {
+            return Err(LoadTextureError::InvalidCubeMap);
+        }
+
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 6,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        for (layer, face) in (0..).zip(faces) {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    aspect: wgpu::TextureAspect::All,
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layer,
+                    },
+                },
+                &face.to_rgba8(),
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * width),
+                    rows_per_image: Some(height),
+                },
+                wgpu::Extent3d {
+                    depth_or_array_layers: 1,
+                    ..size
+                },
+            );
+        }
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Ok(Self {
+            texture,
+            view,
+            sampler,
+        })
+    }
+
+    /// Create a cube map to render into
+    ///
+    /// Every face of every mip level can be rendered into separately, through
+    /// [`CubeTexture::face_view`].
+    pub fn render_target(
+        device: &wgpu::Device,
+        size: u32,
+        mip_level_count: u32,
+        label: Option<&str>,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::RENDER_TARGET_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            texture,
+            view,
+            sampler,
+        }
+    }
+
+    /// A view of a single face of a single mip level
+    pub fn face_view(&self, face: u32, mip_level: u32) -> wgpu::TextureView {
+        self.texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            base_mip_level: mip_level,
+            mip_level_count: Some(1),
+            base_array_layer: face,
+            array_layer_count: Some(1),
+            ..Default::default()
+        })
+    }
+}
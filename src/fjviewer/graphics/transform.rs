@@ -1,6 +1,6 @@
 use bytemuck::{Pod, Zeroable};
 
-use crate::fjviewer::Camera;
+use crate::fjviewer::{to_nalgebra, Camera, Projection};
 
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(transparent)]
@@ -14,18 +14,51 @@ impl Transform {
     /// Compute transform used for vertices
     ///
     /// The returned transform is used for transforming vertices on the GPU.
+    /// It maps depth in reverse: The near plane ends up at a depth of 1, and
+    /// the far plane at 0. In perspective mode, the far plane is infinitely
+    /// far away.
     pub fn for_vertices(camera: &Camera, aspect_ratio: f64) -> Self {
-        let field_of_view_in_y = 2.
-            * ((camera.field_of_view_in_x() / 2.).tan() / aspect_ratio).atan();
+        let near = camera.near_plane();
 
-        let transform = camera.camera_to_model().project_to_array(
-            aspect_ratio,
-            field_of_view_in_y,
-            camera.near_plane(),
-            camera.far_plane(),
-        );
+        #[rustfmt::skip]
+        let projection = match camera.projection() {
+            Projection::Perspective => {
+                let field_of_view_in_y =
+                    camera.field_of_view().vertical(aspect_ratio);
+                let f = 1. / (field_of_view_in_y / 2.).tan();
 
-        Self(transform.map(|scalar| scalar.into_f32()))
+                nalgebra::Matrix4::new(
+                    f / aspect_ratio, 0., 0., 0.,
+                    0., f, 0., 0.,
+                    0., 0., 0., near,
+                    0., 0., -1., 0.,
+                )
+            }
+            Projection::Orthographic => {
+                let width = camera.orthographic_width();
+                let height = width / aspect_ratio;
+                let depth = camera.far_plane() - near;
+
+                nalgebra::Matrix4::new(
+                    2. / width, 0., 0., 0.,
+                    0., 2. / height, 0., 0.,
+                    0., 0., 1. / depth, camera.far_plane() / depth,
+                    0., 0., 0., 1.,
+                )
+            }
+        };
+
+        let matrix = projection * to_nalgebra(&camera.camera_to_model());
+
+        let mut transform = [0.; 16];
+        transform.copy_from_slice(matrix.as_slice());
+
+        Self(transform.map(|value| value as f32))
+    }
+
+    /// Compute transform from model into camera space
+    pub fn for_view(camera: &Camera) -> Self {
+        Self::from(&camera.camera_to_model())
     }
 
     /// Compute transform used for normals
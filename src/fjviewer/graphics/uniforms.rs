@@ -7,6 +7,27 @@ use super::transform::Transform;
 pub struct Uniforms {
     pub transform: Transform,
     pub transform_normals: Transform,
+
+    /// Transforms positions from model into camera space, for lighting
+    pub transform_view: Transform,
+
+    /// The position of the camera in model space, with a `w` of 1
+    ///
+    /// For orthographic projection, this is the direction towards the camera
+    /// instead, with a `w` of 0.
+    pub eye: [f32; 4],
+
+    /// The size of the render target, in pixels
+    pub viewport_size: [f32; 2],
+
+    /// The width of feature edges, in physical pixels
+    pub edge_width: f32,
+
+    /// The cosine of the angle between normals, above which an edge is a
+    /// crease
+    pub crease_cos: f32,
+
+    pub lighting: LightingUniforms,
 }
 
 impl Default for Uniforms {
@@ -14,6 +35,38 @@ impl Default for Uniforms {
         Self {
             transform: Transform::identity(),
             transform_normals: Transform::identity(),
+            transform_view: Transform::identity(),
+            eye: [0., 0., 1., 0.],
+            viewport_size: [1., 1.],
+            edge_width: 1.,
+            crease_cos: 1.,
+            lighting: LightingUniforms::default(),
         }
     }
 }
+
+/// The lights and the material, as passed to the shader
+///
+/// The lights are in camera space. See [`super::Lighting::uniforms`].
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
+#[repr(C)]
+pub struct LightingUniforms {
+    pub ambient: [f32; 4],
+    pub lights: [LightUniforms; super::Lighting::MAX_LIGHTS],
+    pub num_lights: u32,
+    pub specular: f32,
+    pub shininess: f32,
+    pub environment: f32,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub _padding: [f32; 2],
+}
+
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
+#[repr(C)]
+pub struct LightUniforms {
+    /// The direction towards the light, with a `w` of 0, or its position,
+    /// with a `w` of 1
+    pub vector: [f32; 4],
+    pub color: [f32; 4],
+}
@@ -1,3 +1,5 @@
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
 use bytemuck::{Pod, Zeroable};
 use fj_interop::{Index, Mesh};
 
@@ -5,13 +7,22 @@ use fj_interop::{Index, Mesh};
 pub struct Vertices {
     vertices: Vec<Vertex>,
     indices: Vec<Index>,
+
+    /// The edges of the triangles, as a line list
+    edges: Vec<Index>,
+
+    /// Each edge of the triangles once, for drawing creases and silhouettes
+    feature_edges: Vec<FeatureEdge>,
 }
 
 impl Vertices {
+    #[allow(dead_code)]
     pub fn empty() -> Self {
         Self {
             vertices: Vec::new(),
             indices: Vec::new(),
+            edges: Vec::new(),
+            feature_edges: Vec::new(),
         }
     }
 
@@ -22,6 +33,14 @@ impl Vertices {
     pub fn indices(&self) -> &[Index] {
         self.indices.as_slice()
     }
+
+    pub fn edges(&self) -> &[Index] {
+        self.edges.as_slice()
+    }
+
+    pub fn feature_edges(&self) -> &[FeatureEdge] {
+        self.feature_edges.as_slice()
+    }
 }
 
 impl From<&Mesh<fj_math::Point<3>>> for Vertices {
@@ -39,7 +58,7 @@ impl From<&Mesh<fj_math::Point<3>>> for Vertices {
             m.push_vertex((c, normal, color));
         }
 
-        let vertices = m
+        let vertices: Vec<Vertex> = m
             .vertices()
             .map(|(vertex, normal, color)| Vertex {
                 position: vertex.into(),
@@ -48,9 +67,100 @@ impl From<&Mesh<fj_math::Point<3>>> for Vertices {
             })
             .collect();
 
-        let indices = m.indices().collect();
+        let indices: Vec<Index> = m.indices().collect();
+        let edges = edges(&vertices, &indices);
+        let feature_edges = feature_edges(&vertices, &indices);
+
+        Self {
+            vertices,
+            indices,
+            edges,
+            feature_edges,
+        }
+    }
+}
+
+/// Collect the edges of the triangles, so each is only drawn once
+///
+/// Vertices are not shared between triangles that have different normals, so
+/// edges are identified by the positions of their vertices, not by indices.
+fn edges(vertices: &[Vertex], indices: &[Index]) -> Vec<Index> {
+    let mut seen = HashSet::new();
+    let mut edges = Vec::new();
+
+    for triangle in indices.chunks_exact(3) {
+        for [a, b] in triangle_edges(triangle) {
+            if seen.insert(edge_key(vertices, a, b)) {
+                edges.extend([a, b]);
+            }
+        }
+    }
+
+    edges
+}
+
+/// Collect the edges of the triangles, along with the normals of the
+/// triangles next to them
+fn feature_edges(vertices: &[Vertex], indices: &[Index]) -> Vec<FeatureEdge> {
+    // The edges, with the number of triangles they belong to
+    let mut edges: Vec<(FeatureEdge, usize)> = Vec::new();
+    let mut edge_indices = HashMap::new();
+
+    for triangle in indices.chunks_exact(3) {
+        // Every vertex has the normal of its triangle.
+        let normal = vertices[triangle[0] as usize].normal;
+
+        for [a, b] in triangle_edges(triangle) {
+            match edge_indices.entry(edge_key(vertices, a, b)) {
+                Entry::Vacant(entry) => {
+                    entry.insert(edges.len());
+                    edges.push((
+                        FeatureEdge {
+                            a: vertices[a as usize].position,
+                            b: vertices[b as usize].position,
+                            normal_a: normal,
+                            normal_b: normal,
+                            kind: FeatureEdge::OUTLINE,
+                        },
+                        1,
+                    ));
+                }
+                Entry::Occupied(entry) => {
+                    let (edge, num_triangles) = &mut edges[*entry.get()];
+                    *num_triangles += 1;
+
+                    if *num_triangles == 2 {
+                        edge.normal_b = normal;
+                        edge.kind = FeatureEdge::SHARED;
+                    } else {
+                        edge.kind = FeatureEdge::OUTLINE;
+                    }
+                }
+            }
+        }
+    }
+
+    edges.into_iter().map(|(edge, _)| edge).collect()
+}
+
+fn triangle_edges(triangle: &[Index]) -> [[Index; 2]; 3] {
+    [
+        [triangle[0], triangle[1]],
+        [triangle[1], triangle[2]],
+        [triangle[2], triangle[0]],
+    ]
+}
+
+/// Identifies an edge by the positions of its vertices, in either direction
+fn edge_key(vertices: &[Vertex], a: Index, b: Index) -> ([u32; 3], [u32; 3]) {
+    let position =
+        |index: Index| vertices[index as usize].position.map(f32::to_bits);
 
-        Self { vertices, indices }
+    let (a, b) = (position(a), position(b));
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
     }
 }
 
@@ -61,3 +171,119 @@ pub struct Vertex {
     pub normal: [f32; 3],
     pub color: [f32; 4],
 }
+
+/// An edge of the mesh, as drawn by the feature edge renderer
+///
+/// Whether an edge is drawn is decided on the GPU, as that depends on the
+/// view and the configured crease angle.
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct FeatureEdge {
+    pub a: [f32; 3],
+    pub b: [f32; 3],
+
+    /// The normals of the triangles next to the edge
+    ///
+    /// Both are the same, unless the edge is [`FeatureEdge::SHARED`].
+    pub normal_a: [f32; 3],
+    pub normal_b: [f32; 3],
+
+    pub kind: u32,
+}
+
+impl FeatureEdge {
+    /// An edge between two triangles
+    ///
+    /// It is drawn, if the triangles meet at an angle that is greater than the
+    /// crease angle, or if one faces the camera and the other one doesn't.
+    pub const SHARED: u32 = 0;
+
+    /// A boundary edge, or one shared by more than two triangles
+    ///
+    /// It is always drawn.
+    pub const OUTLINE: u32 = 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_interop::Index;
+
+    use super::{edge_key, edges, feature_edges, FeatureEdge, Vertex};
+
+    const CORNERS: [[f32; 3]; 4] =
+        [[0., 0., 0.], [1., 0., 0.], [1., 1., 0.], [0., 1., 0.]];
+
+    // Two triangles that form a quad, with the diagonal from corner 0 to 2
+    const QUAD: [Index; 6] = [0, 1, 2, 0, 2, 3];
+
+    #[test]
+    fn quad_with_shared_vertices() {
+        let vertices = CORNERS.map(|position| vertex(position, [0., 0., 1.]));
+
+        let edges = edges(&vertices, &QUAD);
+        assert_eq!(edges.len(), 5 * 2);
+
+        let feature_edges = feature_edges(&vertices, &QUAD);
+        assert_eq!(kinds(&feature_edges), [4, 1]);
+
+        let diagonal = shared(&feature_edges);
+        assert_eq!(
+            key([diagonal.a, diagonal.b]),
+            key([CORNERS[0], CORNERS[2]])
+        );
+        assert_eq!(diagonal.normal_a, diagonal.normal_b);
+    }
+
+    #[test]
+    fn quad_with_duplicated_vertices() {
+        // Each triangle has vertices of its own, as happens when triangles
+        // have different normals.
+        let normals = [[0., 0., 1.], [0., 1., 0.]];
+        let vertices: Vec<Vertex> = QUAD
+            .iter()
+            .enumerate()
+            .map(|(i, &corner)| {
+                vertex(CORNERS[corner as usize], normals[i / 3])
+            })
+            .collect();
+        let indices: Vec<Index> = (0..6).collect();
+
+        let edges = edges(&vertices, &indices);
+        assert_eq!(edges.len(), 5 * 2);
+
+        let feature_edges = feature_edges(&vertices, &indices);
+        assert_eq!(kinds(&feature_edges), [4, 1]);
+
+        let diagonal = shared(&feature_edges);
+        assert_eq!(
+            key([diagonal.a, diagonal.b]),
+            key([CORNERS[0], CORNERS[2]])
+        );
+        assert_eq!([diagonal.normal_a, diagonal.normal_b], normals);
+    }
+
+    fn vertex(position: [f32; 3], normal: [f32; 3]) -> Vertex {
+        Vertex {
+            position,
+            normal,
+            color: [1.; 4],
+        }
+    }
+
+    /// The number of outline and shared edges
+    fn kinds(edges: &[FeatureEdge]) -> [usize; 2] {
+        [FeatureEdge::OUTLINE, FeatureEdge::SHARED]
+            .map(|kind| edges.iter().filter(|edge| edge.kind == kind).count())
+    }
+
+    fn shared(edges: &[FeatureEdge]) -> &FeatureEdge {
+        edges
+            .iter()
+            .find(|edge| edge.kind == FeatureEdge::SHARED)
+            .unwrap()
+    }
+
+    fn key(positions: [[f32; 3]; 2]) -> ([u32; 3], [u32; 3]) {
+        edge_key(&positions.map(|position| vertex(position, [0.; 3])), 0, 1)
+    }
+}
@@ -0,0 +1,123 @@
+//! Back and forward navigation through previous camera poses
+
+use std::collections::VecDeque;
+
+use super::CameraPose;
+
+/// A bounded history of camera poses
+///
+/// Works like the history of a web browser: Going back to a pose makes the
+/// pose that was left available to go forward to again, until the camera is
+/// moved somewhere new.
+#[derive(Debug, Default)]
+pub struct History {
+    back: VecDeque<CameraPose>,
+    forward: Vec<CameraPose>,
+}
+
+impl History {
+    /// The maximum number of poses that can be gone back to
+    const CAPACITY: usize = 100;
+
+    /// Record the pose that the camera is about to be moved away from
+    pub fn record(&mut self, pose: CameraPose) {
+        self.forward.clear();
+
+        if self.back.back() == Some(&pose) {
+            return;
+        }
+
+        self.back.push_back(pose);
+        if self.back.len() > Self::CAPACITY {
+            self.back.pop_front();
+        }
+    }
+
+    /// Go back to the previously recorded pose
+    ///
+    /// `current` is the pose of the camera right now, which becomes available
+    /// to go forward to.
+    pub fn back(&mut self, current: CameraPose) -> Option<CameraPose> {
+        let pose = self.back.pop_back()?;
+        self.forward.push(current);
+        Some(pose)
+    }
+
+    /// Go forward to the pose that was last gone back from
+    ///
+    /// `current` is the pose of the camera right now, which becomes available
+    /// to go back to.
+    pub fn forward(&mut self, current: CameraPose) -> Option<CameraPose> {
+        let pose = self.forward.pop()?;
+        self.back.push_back(current);
+        Some(pose)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_math::Transform;
+
+    use crate::fjviewer::CameraPose;
+
+    use super::History;
+
+    #[test]
+    fn back_and_forward_round_trip() {
+        let mut history = History::default();
+        history.record(pose(0));
+        history.record(pose(1));
+
+        assert_eq!(history.back(pose(2)), Some(pose(1)));
+        assert_eq!(history.back(pose(1)), Some(pose(0)));
+        assert_eq!(history.back(pose(0)), None);
+
+        assert_eq!(history.forward(pose(0)), Some(pose(1)));
+        assert_eq!(history.forward(pose(1)), Some(pose(2)));
+        assert_eq!(history.forward(pose(2)), None);
+
+        assert_eq!(history.back(pose(2)), Some(pose(1)));
+    }
+
+    #[test]
+    fn recording_clears_forward() {
+        let mut history = History::default();
+        history.record(pose(0));
+        assert_eq!(history.back(pose(1)), Some(pose(0)));
+
+        history.record(pose(0));
+        assert_eq!(history.forward(pose(2)), None);
+        assert_eq!(history.back(pose(2)), Some(pose(0)));
+    }
+
+    #[test]
+    fn oldest_poses_are_dropped_at_capacity() {
+        let mut history = History::default();
+        for i in 0..History::CAPACITY {
+            history.record(pose(i));
+        }
+        assert_eq!(history.back.len(), History::CAPACITY);
+        assert_eq!(history.back.front(), Some(&pose(0)));
+
+        history.record(pose(History::CAPACITY));
+        assert_eq!(history.back.len(), History::CAPACITY);
+        assert_eq!(history.back.front(), Some(&pose(1)));
+
+        let mut current = pose(History::CAPACITY + 1);
+        let mut num_back = 0;
+        while let Some(pose) = history.back(current) {
+            current = pose;
+            num_back += 1;
+        }
+        assert_eq!(num_back, History::CAPACITY);
+        assert_eq!(current, pose(1));
+    }
+
+    fn pose(i: usize) -> CameraPose {
+        CameraPose {
+            rotation: Transform::identity(),
+            translation: Transform::identity(),
+            orthographic_width: i as f64,
+        }
+    }
+}
@@ -1,15 +1,26 @@
-use super::{movement::Movement, rotation::Rotation, zoom::Zoom, InputEvent};
+use super::{
+    movement::Movement, rotation::Rotation, zoom::Zoom, InputEvent,
+    RotationMode,
+};
 use crate::fjviewer::{Camera, FocusPoint};
 
 /// Input handling abstraction
 ///
 /// Takes user input and applies them to application state.
 #[derive(Default)]
-pub struct InputHandler;
+pub struct InputHandler {
+    rotation_mode: RotationMode,
+}
 
 impl InputHandler {
+    /// Set how rotation input rotates the model
+    pub fn set_rotation_mode(&mut self, rotation_mode: RotationMode) {
+        self.rotation_mode = rotation_mode;
+    }
+
     /// Handle an input event
     pub fn handle_event(
+        &self,
         event: InputEvent,
         focus_point: FocusPoint,
         camera: &mut Camera,
@@ -19,7 +30,13 @@ impl InputHandler {
                 Movement::apply(previous, current, focus_point, camera);
             }
             InputEvent::Rotation { angle_x, angle_y } => {
-                Rotation::apply(angle_x, angle_y, focus_point, camera);
+                Rotation::apply(
+                    self.rotation_mode,
+                    angle_x,
+                    angle_y,
+                    focus_point,
+                    camera,
+                );
             }
             InputEvent::Zoom(zoom_delta) => {
                 Zoom::apply(zoom_delta, focus_point, camera);
@@ -6,4 +6,8 @@ mod movement;
 mod rotation;
 mod zoom;
 
-pub use self::{event::InputEvent, handler::InputHandler};
+pub use self::{
+    event::InputEvent,
+    handler::InputHandler,
+    rotation::{Axis, RotationMode},
+};
@@ -1,7 +1,7 @@
 use fj_math::{Point, Scalar, Transform, Vector};
 
 use crate::{
-    fjviewer::{Camera, FocusPoint},
+    fjviewer::{Camera, FocusPoint, Projection},
     fjviewer::NormalizedScreenPosition,
 };
 
@@ -17,10 +17,17 @@ impl Movement {
         let previous = camera.cursor_to_model_space(previous);
         let cursor = camera.cursor_to_model_space(current);
 
-        let d1 = Point::distance_to(&camera.position(), &cursor);
-        let d2 = Point::distance_to(&camera.position(), &focus_point.0);
+        let diff = match camera.projection() {
+            Projection::Perspective => {
+                let d1 = Point::distance_to(&camera.position(), &cursor);
+                let d2 = Point::distance_to(&camera.position(), &focus_point.0);
 
-        let diff = (cursor - previous) * d2 / d1;
+                (cursor - previous) * d2 / d1
+            }
+            // The cursor moves across the model at the same rate, regardless
+            // of depth.
+            Projection::Orthographic => cursor - previous,
+        };
         let offset = camera.camera_to_model().transform_vector(&diff);
 
         camera.translation = camera.translation
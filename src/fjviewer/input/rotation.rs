@@ -1,11 +1,50 @@
-use fj_math::{Transform, Vector};
+use fj_math::{Scalar, Transform, Vector};
 
 use crate::fjviewer::{Camera, FocusPoint};
 
+/// How dragging the mouse rotates the model around the focus point
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RotationMode {
+    /// Rotate freely around the horizontal and vertical axes of the screen
+    #[default]
+    Trackball,
+
+    /// Spin around the model's z axis and tilt around the horizontal axis of
+    /// the screen, so the z axis stays vertical on screen
+    Turntable,
+
+    /// Only rotate around a single axis of the model
+    Orbit(Axis),
+}
+
+/// An axis of the model's coordinate system
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Axis {
+    /// The x axis
+    X,
+
+    /// The y axis
+    Y,
+
+    /// The z axis
+    Z,
+}
+
+impl Axis {
+    fn vector(self) -> Vector<3> {
+        match self {
+            Self::X => Vector::unit_x(),
+            Self::Y => Vector::unit_y(),
+            Self::Z => Vector::unit_z(),
+        }
+    }
+}
+
 pub struct Rotation;
 
 impl Rotation {
     pub fn apply(
+        mode: RotationMode,
         angle_x: f64,
         angle_y: f64,
         focus_point: FocusPoint,
@@ -18,8 +57,46 @@ impl Rotation {
         let right_vector = right_vector(&camera_rotation);
         let up_vector = up_vector(&camera_rotation);
 
-        let rotation = Transform::rotation(right_vector * angle_x)
-            * Transform::rotation(up_vector * angle_y);
+        let rotation = match mode {
+            RotationMode::Trackball => {
+                Transform::rotation(right_vector * angle_x)
+                    * Transform::rotation(up_vector * angle_y)
+            }
+            RotationMode::Turntable => {
+                // Tilt around the horizontal part of the right vector, so the
+                // model never rolls to the side.
+                let tilt_axis = Vector::from([
+                    right_vector.x,
+                    right_vector.y,
+                    Scalar::ZERO,
+                ]);
+                let tilt_axis = if tilt_axis.magnitude().into_f64() > 1e-9 {
+                    tilt_axis.normalize()
+                } else {
+                    right_vector
+                };
+
+                // Keep spinning in the direction of the drag, if the model has
+                // been tilted upside down.
+                let spin = if up_vector.z < Scalar::ZERO {
+                    -angle_y
+                } else {
+                    angle_y
+                };
+
+                Transform::rotation(tilt_axis * angle_x)
+                    * Transform::rotation(Vector::unit_z() * spin)
+            }
+            RotationMode::Orbit(axis) => {
+                // Only keep the part of the trackball rotation that goes
+                // around the locked axis.
+                let axis = axis.vector();
+                let angle = right_vector.dot(&axis) * angle_x
+                    + up_vector.dot(&axis) * angle_y;
+
+                Transform::rotation(axis * angle)
+            }
+        };
 
         let transform = camera.camera_to_model()
             * rotate_around
@@ -1,6 +1,6 @@
 use fj_math::{Transform, Vector};
 
-use crate::fjviewer::{Camera, FocusPoint};
+use crate::fjviewer::{Camera, FocusPoint, Projection};
 
 pub struct Zoom;
 
@@ -10,9 +10,22 @@ impl Zoom {
         focus_point: FocusPoint,
         camera: &mut Camera,
     ) {
-        let distance = (focus_point.0 - camera.position()).magnitude();
-        let displacement = zoom_delta * distance.into_f64();
-        camera.translation = camera.translation
-            * Transform::translation(Vector::from([0.0, 0.0, displacement]));
+        match camera.projection() {
+            Projection::Perspective => {
+                let distance = (focus_point.0 - camera.position()).magnitude();
+                let displacement = zoom_delta * distance.into_f64();
+                camera.translation = camera.translation
+                    * Transform::translation(Vector::from([
+                        0.0,
+                        0.0,
+                        displacement,
+                    ]));
+            }
+            Projection::Orthographic => {
+                // Moving the camera doesn't change the size of the model in
+                // orthographic mode. Scale the view volume instead.
+                camera.scale_orthographic_width(1.0 - zoom_delta);
+            }
+        }
     }
 }
@@ -1,15 +1,38 @@
 //! Viewer camera module
-use std::{f64::consts::FRAC_PI_2, sync::Arc};
+use std::{
+    f64::consts::{FRAC_PI_2, PI},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use fj_interop::{Mesh, Model};
+use fj_interop::Model;
 use fj_math::{Aabb, Point, Scalar, Transform, Vector};
-use iced_wgpu::wgpu::rwh::{HasDisplayHandle,HasWindowHandle};
+use iced_wgpu::wgpu::rwh::{HasDisplayHandle, HasWindowHandle};
 
+// The assets are only used by the navigation cube, which isn't drawn by the
+// shader widget yet.
+#[allow(dead_code)]
+mod assets;
+mod bvh;
 pub mod graphics;
+mod history;
 mod input;
-mod assets;
-// pub mod viewer;
-
+mod standard_view;
+mod transition;
+pub mod viewer;
+
+pub use self::{
+    bvh::{Bvh, Hit},
+    input::{Axis, InputEvent, RotationMode},
+    standard_view::StandardView,
+    viewer::Viewer,
+};
+
+use self::transition::Transition;
+
+// The shader widget renders into the target provided by iced, and doesn't
+// need a screen of its own.
+#[allow(dead_code)]
 /// Needs to be implemented by types that can serve as a screen to render to
 pub trait Screen {
     /// The window
@@ -35,7 +58,65 @@ pub struct NormalizedScreenPosition {
     pub y: f64,
 }
 
+/// The projection used by the camera
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Projection {
+    /// Objects further away from the camera appear smaller
+    #[default]
+    Perspective,
+
+    /// Objects appear at the same size, regardless of their distance
+    ///
+    /// Lines that are parallel in the model stay parallel on screen, which
+    /// makes this the mode of choice for measuring and comparing dimensions.
+    Orthographic,
+}
+
+/// The angle that the camera sees, and the screen axis it is measured along
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FieldOfView {
+    /// The angle between the left and right edges of the view (in radians)
+    Horizontal(f64),
+
+    /// The angle between the top and bottom edges of the view (in radians)
+    Vertical(f64),
+}
+
+impl FieldOfView {
+    /// Returns the horizontal angle, for a view of the given aspect ratio
+    pub fn horizontal(self, aspect_ratio: f64) -> f64 {
+        match self {
+            Self::Horizontal(angle) => angle,
+            Self::Vertical(angle) => {
+                2. * ((angle / 2.).tan() * aspect_ratio).atan()
+            }
+        }
+    }
+
+    /// Returns the vertical angle, for a view of the given aspect ratio
+    pub fn vertical(self, aspect_ratio: f64) -> f64 {
+        match self {
+            Self::Horizontal(angle) => {
+                2. * ((angle / 2.).tan() / aspect_ratio).atan()
+            }
+            Self::Vertical(angle) => angle,
+        }
+    }
+
+    fn is_valid(self) -> bool {
+        let (Self::Horizontal(angle) | Self::Vertical(angle)) = self;
+        angle > 0. && angle < PI
+    }
+}
+
+impl Default for FieldOfView {
+    fn default() -> Self {
+        Self::Horizontal(FRAC_PI_2) // 90 degrees
+    }
+}
+
 /// The size of the screen
+#[allow(dead_code)]
 #[derive(Clone, Copy, Debug)]
 pub struct ScreenSize {
     /// The width of the screen
@@ -45,6 +126,7 @@ pub struct ScreenSize {
     pub height: u32,
 }
 
+#[allow(dead_code)]
 impl ScreenSize {
     /// Convert size to `f64`
     pub fn as_f64(&self) -> [f64; 2] {
@@ -58,7 +140,7 @@ impl ScreenSize {
 /// is handled, for example) is not that of a camera freely flying through a
 /// static scene. Instead, the camera is static, and the model is freely
 /// translated and rotated.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Camera {
     /// The distance to the near plane
     near_plane: f64,
@@ -66,27 +148,59 @@ pub struct Camera {
     /// The distance to the far plane
     far_plane: f64,
 
+    /// The width of the view divided by its height
+    aspect_ratio: f64,
+
+    /// The field of view, if using a perspective projection
+    field_of_view: FieldOfView,
+
+    /// The projection mode
+    projection: Projection,
+
+    /// The width of the view volume, if using an orthographic projection
+    orthographic_width: f64,
+
     /// The rotational part of the transform
     pub rotation: Transform,
 
     /// The locational part of the transform
     pub translation: Transform,
+
+    /// The transition to a standard view that is currently in progress
+    transition: Option<Transition>,
 }
 
 impl Camera {
     const DEFAULT_NEAR_PLANE: f64 = 0.0001;
     const DEFAULT_FAR_PLANE: f64 = 1000.0;
+    const DEFAULT_ORTHOGRAPHIC_WIDTH: f64 = 1.0;
 
-    const INITIAL_FIELD_OF_VIEW_IN_X: f64 = FRAC_PI_2; // 90 degrees
+    /// The fraction of the model's depth left between it and the planes
+    const DEPTH_MARGIN: f64 = 0.01;
+
+    /// The closest the near plane gets to the camera, relative to the far
+    /// side of the model
+    const MIN_NEAR_PLANE_RATIO: f64 = 1e-6;
+
+    /// The fraction of the view left free on each side when framing
+    const FRAME_MARGIN: f64 = 0.05;
 
     /// Returns a new camera aligned for viewing a bounding box
     pub fn new() -> Self {
         Self {
             near_plane: Self::DEFAULT_NEAR_PLANE,
-            far_plane: Self::DEFAULT_FAR_PLANE,
+            far_plane: f64::INFINITY,
+
+            aspect_ratio: 1.,
+            field_of_view: FieldOfView::default(),
+
+            projection: Projection::default(),
+            orthographic_width: Self::DEFAULT_ORTHOGRAPHIC_WIDTH,
 
             rotation: Transform::identity(),
             translation: Transform::identity(),
+
+            transition: None,
         }
     }
 
@@ -95,14 +209,107 @@ impl Camera {
         self.near_plane
     }
 
-    /// Returns the distance between the camera and the maximum distance for rendering..
+    /// Returns the distance between the camera and the maximum distance for rendering.
+    ///
+    /// This is infinite in perspective mode.
     pub fn far_plane(&self) -> f64 {
         self.far_plane
     }
 
+    /// Returns the aspect ratio of the view, i.e. its width divided by its
+    /// height.
+    pub fn aspect_ratio(&self) -> f64 {
+        self.aspect_ratio
+    }
+
+    /// Set the aspect ratio of the view.
+    ///
+    /// Values that aren't finite and positive are ignored.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f64) {
+        if aspect_ratio.is_finite() && aspect_ratio > 0. {
+            self.aspect_ratio = aspect_ratio;
+        }
+    }
+
+    /// Returns the field of view of the camera.
+    pub fn field_of_view(&self) -> FieldOfView {
+        self.field_of_view
+    }
+
     /// Returns the horizontal field of view of the camera.
     pub fn field_of_view_in_x(&self) -> f64 {
-        Self::INITIAL_FIELD_OF_VIEW_IN_X
+        self.field_of_view.horizontal(self.aspect_ratio)
+    }
+
+    /// Returns the vertical field of view of the camera.
+    pub fn field_of_view_in_y(&self) -> f64 {
+        self.field_of_view.vertical(self.aspect_ratio)
+    }
+
+    /// Change the field of view of the camera.
+    ///
+    /// Angles outside of the open range from 0 to 180 degrees are ignored.
+    /// With a `dolly_zoom_target`, the camera is moved along the view
+    /// direction, so the target keeps its size on screen in perspective mode,
+    /// while the perspective distortion around it changes.
+    pub fn set_field_of_view(
+        &mut self,
+        field_of_view: FieldOfView,
+        dolly_zoom_target: Option<Point<3>>,
+    ) {
+        if !field_of_view.is_valid() || field_of_view == self.field_of_view {
+            return;
+        }
+
+        let target = dolly_zoom_target
+            .filter(|_| self.projection == Projection::Perspective)
+            .map(|target| (target, self.depth_of(target)))
+            .filter(|&(_, distance)| distance > 0.);
+        let width = target.map(|(_, distance)| self.width_at_depth(distance));
+
+        self.field_of_view = field_of_view;
+
+        if let (Some((target, _)), Some(width)) = (target, width) {
+            self.move_to_width_at(target, width);
+        }
+    }
+
+    /// Returns the projection mode of the camera.
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    /// Switch the projection mode of the camera.
+    ///
+    /// The size of the view volume is chosen such, that `target` appears at
+    /// the same size on screen before and after the switch.
+    pub fn set_projection(&mut self, projection: Projection, target: Point<3>) {
+        if projection == self.projection {
+            return;
+        }
+
+        if projection == Projection::Orthographic {
+            let distance = self.depth_of(target);
+            if distance > 0. {
+                self.orthographic_width = self.width_at_depth(distance);
+            }
+        } else {
+            self.move_to_width_at(target, self.orthographic_width);
+        }
+
+        self.projection = projection;
+    }
+
+    /// Returns the width of the view volume in orthographic mode.
+    pub fn orthographic_width(&self) -> f64 {
+        self.orthographic_width
+    }
+
+    /// Scale the width of the view volume in orthographic mode.
+    pub fn scale_orthographic_width(&mut self, factor: f64) {
+        if factor > 0. {
+            self.orthographic_width *= factor;
+        }
     }
 
     /// Returns the position of the camera in world space.
@@ -111,55 +318,258 @@ impl Camera {
             .inverse_transform_point(&Point::<3>::origin())
     }
 
+    /// Snap to a standard view
+    ///
+    /// `target` ends up in the center of the screen, at the same distance from
+    /// the camera as before.
+    pub fn set_view(&mut self, view: StandardView, target: Point<3>) {
+        self.transition = None;
+        (self.rotation, self.translation) = self.view_transform(view, target);
+    }
+
+    /// Start an animated transition to a standard view
+    ///
+    /// Behaves like [`Camera::set_view`] once the transition is finished. Call
+    /// [`Camera::advance_transition`] on every frame to drive the animation.
+    /// A duration of zero snaps to the view immediately.
+    pub fn transition_to(
+        &mut self,
+        view: StandardView,
+        target: Point<3>,
+        duration: Duration,
+        now: Instant,
+    ) {
+        if duration.is_zero() {
+            self.set_view(view, target);
+            return;
+        }
+
+        let (rotation, translation) = self.view_transform(view, target);
+        self.start_transition(rotation, translation, target, duration, now);
+    }
+
+    /// Returns the current position and orientation of the camera
+    pub fn pose(&self) -> CameraPose {
+        CameraPose {
+            rotation: self.rotation,
+            translation: self.translation,
+            orthographic_width: self.orthographic_width,
+        }
+    }
+
+    /// Start an animated transition to a previously recorded pose
+    ///
+    /// The camera moves such that `target` doesn't wander across the screen.
+    /// The width of the orthographic view volume isn't animated, and changes
+    /// immediately. A duration of zero snaps to the pose immediately.
+    pub fn transition_to_pose(
+        &mut self,
+        pose: CameraPose,
+        target: Point<3>,
+        duration: Duration,
+        now: Instant,
+    ) {
+        self.orthographic_width = pose.orthographic_width;
+
+        if duration.is_zero() {
+            self.transition = None;
+            self.rotation = pose.rotation;
+            self.translation = pose.translation;
+            return;
+        }
+
+        self.start_transition(
+            pose.rotation,
+            pose.translation,
+            target,
+            duration,
+            now,
+        );
+    }
+
+    fn start_transition(
+        &mut self,
+        rotation: Transform,
+        translation: Transform,
+        target: Point<3>,
+        duration: Duration,
+        now: Instant,
+    ) {
+        self.transition = Some(Transition::new(
+            (&self.rotation, &self.translation),
+            (&rotation, &translation),
+            target,
+            now,
+            duration,
+        ));
+    }
+
+    /// Indicate whether a transition is currently in progress
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    /// Move the camera along the current transition, if there is one
+    ///
+    /// Returns `true`, if the camera has been moved.
+    pub fn advance_transition(&mut self, now: Instant) -> bool {
+        let Some(transition) = &self.transition else {
+            return false;
+        };
+
+        let (rotation, translation, is_finished) = transition.sample(now);
+        self.rotation = rotation;
+        self.translation = translation;
+
+        if is_finished {
+            self.transition = None;
+        }
+
+        true
+    }
+
+    /// Abort the current transition, leaving the camera where it is
+    pub fn cancel_transition(&mut self) {
+        self.transition = None;
+    }
+
+    fn view_transform(
+        &self,
+        view: StandardView,
+        target: Point<3>,
+    ) -> (Transform, Transform) {
+        let rotation = view.rotation();
+
+        let distance = self.depth_of(target);
+        let offset = Vector::from([0., 0., -distance])
+            - rotation.transform_point(&target).coords;
+
+        (rotation, Transform::translation(offset))
+    }
+
     /// Transform a normalized cursor position on the near plane to model space.
     pub fn cursor_to_model_space(
         &self,
         cursor: NormalizedScreenPosition,
     ) -> Point<3> {
         // Cursor position in camera space.
-        let f = (self.field_of_view_in_x() / 2.).tan() * self.near_plane();
+        let f = self.width_at_depth(self.near_plane()) / 2.;
         let cursor = Point::origin()
             + Vector::from([cursor.x * f, cursor.y * f, -self.near_plane()]);
 
         self.camera_to_model().inverse_transform_point(&cursor)
     }
 
+    /// Project a point in model space onto the screen.
+    ///
+    /// Returns the normalized screen position of the point, and its depth,
+    /// i.e. its distance from the camera along the view direction. This is
+    /// the inverse of [`Camera::cursor_ray`]. In perspective mode, there is no
+    /// sensible screen position for points at or behind the camera, and
+    /// `None` is returned for those.
+    pub fn model_to_screen(
+        &self,
+        point: Point<3>,
+    ) -> Option<(NormalizedScreenPosition, f64)> {
+        let point = self.camera_to_model().transform_point(&point);
+        let [x, y, z] = [point.x, point.y, point.z].map(Scalar::into_f64);
+        let depth = -z;
+
+        if self.projection == Projection::Perspective && depth <= 0. {
+            return None;
+        }
+        let f = self.width_at_depth(depth) / 2.;
+
+        Some((NormalizedScreenPosition { x: x / f, y: y / f }, depth))
+    }
+
+    /// Compute the ray through a normalized cursor position in model space.
+    ///
+    /// Returns the origin and the normalized direction of the ray. In
+    /// perspective mode, all rays start at the camera position. In
+    /// orthographic mode, they are parallel to the view direction and start
+    /// on the near plane, which may be behind the camera.
+    pub fn cursor_ray(
+        &self,
+        cursor: NormalizedScreenPosition,
+    ) -> (Point<3>, Vector<3>) {
+        match self.projection {
+            Projection::Perspective => {
+                let origin = self.position();
+                let dir =
+                    (self.cursor_to_model_space(cursor) - origin).normalize();
+
+                (origin, dir)
+            }
+            Projection::Orthographic => {
+                let f = self.orthographic_width / 2.;
+                let origin = Point::origin()
+                    + Vector::from([
+                        cursor.x * f,
+                        cursor.y * f,
+                        -self.near_plane(),
+                    ]);
+
+                let model_to_camera = self.camera_to_model().inverse();
+                let origin = model_to_camera.transform_point(&origin);
+                let dir = model_to_camera
+                    .transform_vector(&Vector::from([0., 0., -1.]))
+                    .normalize();
+
+                (origin, dir)
+            }
+        }
+    }
+
+    /// Move the camera along the view direction, until the perspective view
+    /// volume has the given width at `target`.
+    fn move_to_width_at(&mut self, target: Point<3>, width: f64) {
+        let distance = width / 2. / (self.field_of_view_in_x() / 2.).tan();
+        let displacement = self.depth_of(target) - distance;
+        self.translation = self.translation
+            * Transform::translation(Vector::from([0., 0., displacement]));
+    }
+
+    /// Returns the width of the view volume at the given depth.
+    fn width_at_depth(&self, depth: f64) -> f64 {
+        match self.projection {
+            Projection::Perspective => {
+                (self.field_of_view_in_x() / 2.).tan() * depth * 2.
+            }
+            Projection::Orthographic => self.orthographic_width,
+        }
+    }
+
+    /// Returns the distance of a point in model space from the camera, along
+    /// the view direction.
+    fn depth_of(&self, point: Point<3>) -> f64 {
+        -self.camera_to_model().transform_point(&point).z.into_f64()
+    }
+
     /// Compute the point on the model, that the cursor currently points to.
     pub fn focus_point(
         &self,
         cursor: Option<NormalizedScreenPosition>,
         model: &Model,
+        bvh: &Bvh,
     ) -> FocusPoint {
-        self.calculate_focus_point(cursor, &model.mesh)
+        cursor
+            .and_then(|cursor| self.pick(cursor, bvh))
+            .map(|hit| FocusPoint(hit.point))
             .unwrap_or_else(|| FocusPoint(model.aabb.center()))
     }
 
-    fn calculate_focus_point(
+    /// Find the triangle of the model that the cursor points to.
+    ///
+    /// `bvh` must have been built from the mesh of the model. The distance of
+    /// the hit is measured from the origin of [`Camera::cursor_ray`].
+    pub fn pick(
         &self,
-        cursor: Option<NormalizedScreenPosition>,
-        mesh: &Mesh<Point<3>>,
-    ) -> Option<FocusPoint> {
-        // Transform camera and cursor positions to model space.
-        let origin = self.position();
-        let cursor = self.cursor_to_model_space(cursor?);
-        let dir = (cursor - origin).normalize();
-
-        let mut min_t = None;
-
-        for triangle in mesh.triangles() {
-            let t =
-                triangle
-                    .inner
-                    .cast_local_ray(origin, dir, f64::INFINITY, true);
-
-            if let Some(t) = t {
-                if t <= min_t.unwrap_or(t) {
-                    min_t = Some(t);
-                }
-            }
-        }
-
-        Some(FocusPoint(origin + dir * min_t?))
+        cursor: NormalizedScreenPosition,
+        bvh: &Bvh,
+    ) -> Option<Hit> {
+        let (origin, dir) = self.cursor_ray(cursor);
+        bvh.cast_ray(origin, dir)
     }
 
     /// Access the transform from camera to model space.
@@ -174,74 +584,115 @@ impl Camera {
         transform
     }
 
-    /// Initialize the planes
+    /// Move the camera, so the bounding box fills the view
     ///
-    /// Call this, if a shape is available for the first time.
-    pub fn init_planes(&mut self, aabb: &Aabb<3>) {
-        let initial_distance = {
-            // Let's make sure we choose a distance, so that the model fills
-            // most of the screen.
-            //
-            // To do that, first compute the model's highest point, as well
-            // as the furthest point from the origin, in x and y.
-            let highest_point = aabb.max.z;
-            let furthest_point =
-                [aabb.min.x.abs(), aabb.max.x, aabb.min.y.abs(), aabb.max.y]
-                    .into_iter()
-                    .reduce(Scalar::max)
-                    // `reduce` can only return `None`, if there are no items in
-                    // the iterator. And since we're creating an array full of
-                    // items above, we know this can't panic.
-                    .expect("Array should have contained items");
-
-            // The actual furthest point is not far enough. We don't want
-            // the model to fill the whole screen.
-            let furthest_point = furthest_point * 2.;
-
-            // Having computed those points, figuring out how far the camera
-            // needs to be from the model is just a bit of trigonometry.
-            let distance_from_model =
-                furthest_point / (Self::INITIAL_FIELD_OF_VIEW_IN_X / 2.).atan();
-
-            // And finally, the distance from the origin is trivial now.
-            highest_point + distance_from_model
-        };
+    /// See [`Camera::frame`].
+    pub fn fit_to(&mut self, aabb: &Aabb<3>) {
+        self.frame(aabb.vertices());
+    }
 
-        let initial_offset = {
-            let mut offset = aabb.center();
-            offset.z = Scalar::ZERO;
-            -offset
+    /// Move the camera, so all of the given points are in view
+    ///
+    /// The current rotation is kept. The camera is moved sideways to center
+    /// the points and, in perspective mode, moved to the exact distance at
+    /// which the points fit into the field of view. In
+    /// orthographic mode, the view volume is resized instead. Either way, a
+    /// small margin is left around the points.
+    pub fn frame(&mut self, points: impl IntoIterator<Item = Point<3>>) {
+        // Points in a frame of reference that is rotated like the camera, but
+        // not translated. Framing then comes down to finding a translation.
+        let points: Vec<[f64; 3]> = points
+            .into_iter()
+            .map(|point| {
+                let point = self.rotation.transform_point(&point);
+                [point.x, point.y, point.z].map(Scalar::into_f64)
+            })
+            .collect();
+
+        let Some(z_max) = points.iter().map(|[_, _, z]| *z).reduce(f64::max)
+        else {
+            return;
         };
 
-        let translation = Transform::translation([
-            initial_offset.x,
-            initial_offset.y,
-            -initial_distance,
-        ]);
+        self.transition = None;
 
-        self.translation = translation;
+        let fill = 1. - Self::FRAME_MARGIN * 2.;
+
+        let [x, y, distance] = match self.projection {
+            Projection::Perspective => {
+                // A point is in view, if `|x + t| <= k * (d - z)` holds for
+                // both screen axes, where `t` is the sideways offset, `d` the
+                // distance of the camera, and `k` the tangent of half the
+                // field of view along that axis. Solving for the smallest `d`
+                // at which there is an offset that satisfies this for all
+                // points gives us the exact fit.
+                let fit = |k: f64, coord: usize| {
+                    let mut a = f64::INFINITY;
+                    let mut b = f64::INFINITY;
+
+                    for point in &points {
+                        a = a.min(-point[coord] - k * point[2]);
+                        b = b.min(point[coord] - k * point[2]);
+                    }
+
+                    let distance = -(a + b) / (2. * k);
+                    let offset = (a - b) / 2.;
+
+                    (distance, offset)
+                };
+
+                let k_x = (self.field_of_view_in_x() / 2.).tan() * fill;
+                let k_y = (self.field_of_view_in_y() / 2.).tan() * fill;
+
+                let (distance_x, x) = fit(k_x, 0);
+                let (distance_y, y) = fit(k_y, 1);
+
+                // Don't put the camera into the points, if they are flat and
+                // facing it.
+                let distance = distance_x
+                    .max(distance_y)
+                    .max(z_max + Self::DEFAULT_NEAR_PLANE);
+
+                [x, y, distance]
+            }
+            Projection::Orthographic => {
+                let range = |coord: usize| {
+                    points.iter().fold(
+                        (f64::INFINITY, f64::NEG_INFINITY),
+                        |(min, max), point| {
+                            (min.min(point[coord]), max.max(point[coord]))
+                        },
+                    )
+                };
+
+                let (x_min, x_max) = range(0);
+                let (y_min, y_max) = range(1);
+
+                let width = (x_max - x_min)
+                    .max((y_max - y_min) * self.aspect_ratio)
+                    / fill;
+                if width > 0. {
+                    self.orthographic_width = width;
+                }
+
+                // Distance doesn't affect the size of the model here. Just
+                // make sure the camera ends up in front of it.
+                let distance = z_max + self.orthographic_width;
+
+                [-(x_min + x_max) / 2., -(y_min + y_max) / 2., distance]
+            }
+        };
+
+        self.translation = Transform::translation([x, y, -distance]);
     }
 
     /// Update the max and minimum rendering distance for this camera.
     pub fn update_planes(&mut self, aabb: &Aabb<3>) {
-        let view_transform: Transform = self.camera_to_model();
-        let view_direction = Vector::from([0., 0., -1.]);
-
         let mut dist_min = f64::INFINITY;
         let mut dist_max = f64::NEG_INFINITY;
 
         for vertex in aabb.vertices() {
-            let point = view_transform.transform_point(&vertex);
-
-            // Project `point` onto `view_direction`. See this Wikipedia page:
-            // https://en.wikipedia.org/wiki/Vector_projection
-            //
-            // Let's rename the variables first, so they fit the names in that
-            // page.
-            let (a, b) = (point.coords, view_direction);
-            let a1 = b * a.dot(&b) / b.dot(&b);
-
-            let dist = a1.magnitude().into_f64();
+            let dist = self.depth_of(vertex);
 
             if dist < dist_min {
                 dist_min = dist;
@@ -251,20 +702,42 @@ impl Camera {
             }
         }
 
-        self.near_plane = if dist_min > 0. {
-            // Setting `self.near_plane` to `dist_min` should theoretically
-            // work, but results in the front of the model being clipped. I
-            // wasn't able to figure out why, and for the time being, this
-            // factor seems to work well enough.
-            dist_min * 0.5
-        } else {
-            Self::DEFAULT_NEAR_PLANE
-        };
-        self.far_plane = if dist_max > 0. {
-            dist_max
-        } else {
-            Self::DEFAULT_FAR_PLANE
-        };
+        match self.projection {
+            Projection::Perspective => {
+                // With a reverse-Z depth buffer, precision hardly depends on
+                // the position of the near plane, and the far plane can be
+                // infinitely far away. So the near plane can hug the model,
+                // with the margin only guarding against rounding errors for
+                // faces that lie on the bounding box.
+                let margin = (dist_max - dist_min) * Self::DEPTH_MARGIN;
+
+                self.near_plane = if dist_min - margin > 0. {
+                    dist_min - margin
+                } else if dist_max > 0. {
+                    // The camera is inside the bounding box.
+                    dist_max * Self::MIN_NEAR_PLANE_RATIO
+                } else {
+                    Self::DEFAULT_NEAR_PLANE
+                };
+                self.far_plane = f64::INFINITY;
+            }
+            Projection::Orthographic => {
+                // Depth is linear in orthographic mode, and nothing prevents
+                // the planes from being behind the camera. A small margin
+                // keeps the faces of the bounding box from being clipped due
+                // to rounding errors.
+                let margin = ((dist_max - dist_min) * Self::DEPTH_MARGIN)
+                    .max(Self::DEFAULT_NEAR_PLANE);
+
+                if dist_min.is_finite() && dist_max.is_finite() {
+                    self.near_plane = dist_min - margin;
+                    self.far_plane = dist_max + margin;
+                } else {
+                    self.near_plane = Self::DEFAULT_NEAR_PLANE;
+                    self.far_plane = Self::DEFAULT_FAR_PLANE;
+                }
+            }
+        }
     }
 }
 
@@ -274,9 +747,84 @@ impl Default for Camera {
     }
 }
 
+/// The position and orientation of the camera at a point in time
+///
+/// See [`Camera::pose`] and [`Camera::transition_to_pose`].
+#[derive(Clone, Copy, Debug)]
+pub struct CameraPose {
+    rotation: Transform,
+    translation: Transform,
+    orthographic_width: f64,
+}
+
+impl PartialEq for CameraPose {
+    fn eq(&self, other: &Self) -> bool {
+        self.rotation.data() == other.rotation.data()
+            && self.translation.data() == other.translation.data()
+            && self.orthographic_width == other.orthographic_width
+    }
+}
+
 /// The point around which camera movement happens.
 ///
 /// This will be the point on the model that the cursor is currently pointing at if such a point exists,
 /// falling back to the center point of the model's bounding volume otherwise.
 #[derive(Clone, Copy, Debug)]
-pub struct FocusPoint(pub Point<3>);
\ No newline at end of file
+pub struct FocusPoint(pub Point<3>);
+
+/// Convert a transform into a matrix of this crate's `nalgebra` version
+///
+/// `fj_math` and this crate don't share a `nalgebra` version, so this goes
+/// through the raw column-major matrix data.
+pub fn to_nalgebra(transform: &Transform) -> nalgebra::Matrix4<f64> {
+    nalgebra::Matrix4::from_column_slice(transform.data())
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_math::{Aabb, Point};
+
+    use super::{Camera, Projection, StandardView};
+
+    #[test]
+    fn fitted_bounding_box_is_in_view() {
+        let aabb = Aabb {
+            min: Point::from([-1., 2., -3.]),
+            max: Point::from([4., 3., 0.5]),
+        };
+
+        for projection in [Projection::Perspective, Projection::Orthographic] {
+            for view in [StandardView::Front, StandardView::Isometric] {
+                let mut camera = Camera::new();
+                camera.set_aspect_ratio(1.5);
+                camera.set_projection(projection, Point::origin());
+                camera.set_view(view, Point::origin());
+                camera.fit_to(&aabb);
+
+                let mut extent: f64 = 0.;
+                for vertex in aabb.vertices() {
+                    let (position, depth) =
+                        camera.model_to_screen(vertex).unwrap();
+
+                    // Normalized y coordinates are scaled by the aspect
+                    // ratio.
+                    let [x, y] = [position.x, position.y * 1.5];
+                    assert!(
+                        x.abs() <= 1. && y.abs() <= 1. && depth > 0.,
+                        "{vertex:?} out of view: {x}, {y}, {depth} \
+                        ({projection:?}, {view:?})",
+                    );
+
+                    extent = extent.max(x.abs()).max(y.abs());
+                }
+
+                // The box fills the view, except for the margin.
+                let fill = 1. - Camera::FRAME_MARGIN * 2.;
+                assert!(
+                    (extent - fill).abs() < 1e-9,
+                    "{extent} ({projection:?}, {view:?})",
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+use fj_math::{Transform, Vector};
+
+/// A named, axis-aligned view onto the model
+///
+/// The model's z axis points up in all views except [`StandardView::Top`] and
+/// [`StandardView::Bottom`], which look along the z axis with the y axis
+/// pointing up or down on screen, respectively.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StandardView {
+    /// Looking at the model from the negative y direction
+    Front,
+
+    /// Looking at the model from the positive y direction
+    Back,
+
+    /// Looking at the model from the negative x direction
+    Left,
+
+    /// Looking at the model from the positive x direction
+    Right,
+
+    /// Looking down on the model from the positive z direction
+    Top,
+
+    /// Looking up at the model from the negative z direction
+    Bottom,
+
+    /// Looking at the model from the front, right and top at the same time
+    Isometric,
+}
+
+impl StandardView {
+    /// Returns the rotational part of the camera transform for this view.
+    pub fn rotation(&self) -> Transform {
+        // The identity rotation is the top view. All other views are derived
+        // by first turning the model around its z axis, then tilting it
+        // around the x axis of the screen.
+        let (tilt, turn) = match self {
+            Self::Front => (-FRAC_PI_2, 0.),
+            Self::Back => (-FRAC_PI_2, PI),
+            Self::Left => (-FRAC_PI_2, FRAC_PI_2),
+            Self::Right => (-FRAC_PI_2, -FRAC_PI_2),
+            Self::Top => (0., 0.),
+            Self::Bottom => (PI, 0.),
+            Self::Isometric => (-(1. / 3_f64.sqrt()).acos(), -FRAC_PI_4),
+        };
+
+        Transform::rotation(Vector::from([tilt, 0., 0.]))
+            * Transform::rotation(Vector::from([0., 0., turn]))
+    }
+}
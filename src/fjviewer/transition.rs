@@ -0,0 +1,168 @@
+use std::time::{Duration, Instant};
+
+use fj_math::{Point, Transform, Vector};
+use nalgebra::{Matrix3, Rotation3, UnitQuaternion, Vector3};
+
+use super::to_nalgebra;
+
+/// An animated transition between two camera transforms
+///
+/// The rotation is interpolated along the shortest arc between two
+/// quaternions. The translation follows from it, such that a target point
+/// moves along a straight line in camera space. If the target is in the center
+/// of the screen at the start and the end, it stays there throughout. Both
+/// follow the same ease-in-out curve.
+#[derive(Clone, Debug)]
+pub struct Transition {
+    from_rotation: UnitQuaternion<f64>,
+    to_rotation: UnitQuaternion<f64>,
+
+    /// The target, in model space
+    target: Vector3<f64>,
+
+    /// The target, in camera space at the start and the end
+    from_target: Vector3<f64>,
+    to_target: Vector3<f64>,
+
+    start: Instant,
+    duration: Duration,
+}
+
+impl Transition {
+    /// Start a new transition from one rotation and translation to another
+    ///
+    /// The transition is centered on `target`, usually the point that the
+    /// camera looks at.
+    pub fn new(
+        from: (&Transform, &Transform),
+        to: (&Transform, &Transform),
+        target: Point<3>,
+        start: Instant,
+        duration: Duration,
+    ) -> Self {
+        let (from_rotation, from_translation) = from;
+        let (to_rotation, to_translation) = to;
+
+        let from_rotation = to_quaternion(from_rotation);
+        let to_rotation = to_quaternion(to_rotation);
+        let [x, y, z] = target.coords.components.map(|s| s.into_f64());
+        let target = Vector3::new(x, y, z);
+
+        Self {
+            from_rotation,
+            to_rotation,
+            target,
+            from_target: from_rotation * target
+                + to_translation_vector(from_translation),
+            to_target: to_rotation * target
+                + to_translation_vector(to_translation),
+            start,
+            duration,
+        }
+    }
+
+    /// Returns the rotation and translation at the given point in time
+    ///
+    /// The last element of the returned tuple is `true`, once the transition
+    /// has reached its end.
+    pub fn sample(&self, now: Instant) -> (Transform, Transform, bool) {
+        let elapsed = now.saturating_duration_since(self.start);
+        let t = if self.duration.is_zero() {
+            1.
+        } else {
+            (elapsed.as_secs_f64() / self.duration.as_secs_f64()).min(1.)
+        };
+        let is_finished = t >= 1.;
+
+        // Smoothstep, so the camera neither jumps into motion, nor stops
+        // abruptly.
+        let t = t * t * (3. - 2. * t);
+
+        let delta = self.from_rotation.rotation_to(&self.to_rotation);
+        let rotation =
+            UnitQuaternion::from_scaled_axis(delta.scaled_axis() * t)
+                * self.from_rotation;
+        let translation =
+            self.from_target.lerp(&self.to_target, t) - rotation * self.target;
+
+        let axis_angle = rotation.scaled_axis();
+        let rotation = Transform::rotation(Vector::from([
+            axis_angle.x,
+            axis_angle.y,
+            axis_angle.z,
+        ]));
+        let translation = Transform::translation(Vector::from([
+            translation.x,
+            translation.y,
+            translation.z,
+        ]));
+
+        (rotation, translation, is_finished)
+    }
+}
+
+fn to_quaternion(rotation: &Transform) -> UnitQuaternion<f64> {
+    let matrix = to_nalgebra(rotation);
+    let matrix: Matrix3<f64> = matrix.fixed_view::<3, 3>(0, 0).into_owned();
+
+    UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(
+        matrix,
+    ))
+}
+
+fn to_translation_vector(translation: &Transform) -> Vector3<f64> {
+    to_nalgebra(translation)
+        .fixed_view::<3, 1>(0, 3)
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        f64::consts::FRAC_PI_2,
+        time::{Duration, Instant},
+    };
+
+    use fj_math::{Point, Transform, Vector};
+
+    use super::Transition;
+
+    #[test]
+    fn target_stays_in_the_center() {
+        let target = Point::from([10., 5., 3.]);
+        let distance = Vector::from([0., 0., -20.]);
+
+        // Puts the target straight ahead of the camera, after rotating.
+        let pose = |rotation: Transform| {
+            let translation =
+                distance - rotation.transform_point(&target).coords;
+            (rotation, Transform::translation(translation))
+        };
+        let from = pose(Transform::identity());
+        let to = pose(Transform::rotation(Vector::from([0., FRAC_PI_2, 0.])));
+
+        let start = Instant::now();
+        let duration = Duration::from_secs(2);
+        let transition = Transition::new(
+            (&from.0, &from.1),
+            (&to.0, &to.1),
+            target,
+            start,
+            duration,
+        );
+
+        let (rotation, translation, is_finished) =
+            transition.sample(start + duration / 2);
+        let camera_to_model = translation * rotation;
+        let [x, y, z] = camera_to_model
+            .transform_point(&target)
+            .coords
+            .components
+            .map(|s| s.into_f64());
+
+        assert!(!is_finished);
+        assert!(x.abs() < 1e-9, "{x}");
+        assert!(y.abs() < 1e-9, "{y}");
+        assert!((z + 20.).abs() < 1e-9, "{z}");
+    }
+}
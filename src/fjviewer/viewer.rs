@@ -0,0 +1,301 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use fj_interop::Model;
+use fj_math::Point;
+
+use crate::fjviewer::{
+    graphics::DrawConfig, history::History, input::InputHandler, Bvh, Camera,
+    CameraPose, FieldOfView, FocusPoint, Hit, InputEvent,
+    NormalizedScreenPosition, Projection, RotationMode, StandardView,
+};
+
+/// The Fornjot model viewer
+///
+/// Holds the camera and input state of a single viewer widget. Rendering is
+/// left to the widget's primitive, which takes a snapshot of the camera on
+/// every draw.
+pub struct Viewer {
+    bvh: Option<Arc<Bvh>>,
+    camera: Camera,
+    cursor: Option<NormalizedScreenPosition>,
+    draw_config: DrawConfig,
+    focus_point: Option<FocusPoint>,
+    history: History,
+    input_handler: InputHandler,
+    model: Option<Arc<Model>>,
+
+    /// The camera pose at the start of the current interaction
+    ///
+    /// An interaction lasts as long as a focus point is set, so a whole drag
+    /// ends up as a single entry in the history.
+    pose_before_interaction: Option<CameraPose>,
+
+    /// The duration of transitions between views
+    transition_duration: Duration,
+}
+
+impl Viewer {
+    /// The duration of transitions between views, unless configured
+    /// otherwise
+    pub const DEFAULT_TRANSITION_DURATION: Duration =
+        Duration::from_millis(300);
+
+    /// Construct a new instance of `Viewer`
+    pub fn new() -> Self {
+        Self {
+            bvh: None,
+            camera: Camera::default(),
+            cursor: None,
+            draw_config: DrawConfig::default(),
+            focus_point: None,
+            history: History::default(),
+            input_handler: InputHandler::default(),
+            model: None,
+            pose_before_interaction: None,
+            transition_duration: Self::DEFAULT_TRANSITION_DURATION,
+        }
+    }
+
+    /// Access the camera
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    /// Access the bounding volume hierarchy of the current model
+    pub fn bvh(&self) -> Option<&Arc<Bvh>> {
+        self.bvh.as_ref()
+    }
+
+    /// Access the cursor
+    pub fn cursor(&mut self) -> &mut Option<NormalizedScreenPosition> {
+        &mut self.cursor
+    }
+
+    /// Access the draw configuration
+    pub fn draw_config(&self) -> &DrawConfig {
+        &self.draw_config
+    }
+
+    /// Set what is drawn
+    pub fn set_draw_config(&mut self, draw_config: DrawConfig) {
+        self.draw_config = draw_config;
+    }
+
+    /// Set the aspect ratio of the view, i.e. its width divided by its height
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f64) {
+        self.camera.set_aspect_ratio(aspect_ratio);
+    }
+
+    /// Change the field of view of the camera
+    ///
+    /// With `dolly_zoom`, the camera moves so the focus point, or the center
+    /// of the model if there is none, keeps its size on screen.
+    pub fn set_field_of_view(
+        &mut self,
+        field_of_view: FieldOfView,
+        dolly_zoom: bool,
+    ) {
+        if field_of_view == self.camera.field_of_view() {
+            return;
+        }
+
+        let target = self
+            .focus_point
+            .map(|FocusPoint(point)| point)
+            .unwrap_or_else(|| self.target());
+        self.camera
+            .set_field_of_view(field_of_view, dolly_zoom.then_some(target));
+        self.update_planes();
+    }
+
+    /// Move the camera, so the whole model fills the view
+    pub fn fit(&mut self) {
+        self.history.record(self.camera.pose());
+        self.fit_without_recording();
+    }
+
+    /// Like [`Viewer::fit`], but without recording the current pose
+    ///
+    /// Used to frame the first model. The pose before that doesn't show
+    /// anything, so there is no point in going back to it.
+    fn fit_without_recording(&mut self) {
+        if let Some(model) = &self.model {
+            self.camera.fit_to(&model.aabb);
+        }
+        self.update_planes();
+    }
+
+    /// Move the camera, so the triangle under the cursor fills the view
+    pub fn frame_hovered(&mut self) {
+        let (Some(model), Some(hit)) = (&self.model, self.pick()) else {
+            return;
+        };
+        self.history.record(self.camera.pose());
+
+        let triangle = model.mesh.triangles().nth(hit.triangle_index);
+        if let Some(triangle) = triangle {
+            self.camera.frame(triangle.inner.points());
+        }
+        self.update_planes();
+    }
+
+    /// Find the point on the model that the cursor points to
+    pub fn pick(&self) -> Option<Hit> {
+        self.camera.pick(self.cursor?, self.bvh.as_deref()?)
+    }
+
+    /// Switch the projection mode of the camera
+    ///
+    /// The model keeps its apparent size across the switch.
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.camera.set_projection(projection, self.target());
+        self.update_planes();
+    }
+
+    /// Set how rotation input rotates the model
+    pub fn set_rotation_mode(&mut self, rotation_mode: RotationMode) {
+        self.input_handler.set_rotation_mode(rotation_mode);
+    }
+
+    /// Set how long transitions between views take
+    ///
+    /// A duration of zero makes the camera jump to the new view.
+    pub fn set_transition_duration(&mut self, duration: Duration) {
+        self.transition_duration = duration;
+    }
+
+    /// Move the camera to a standard view, centered on the model
+    ///
+    /// The camera is animated towards the view. Call [`Viewer::advance`] on
+    /// every frame to drive the animation.
+    pub fn show_view(&mut self, view: StandardView, now: Instant) {
+        self.history.record(self.camera.pose());
+        self.camera.transition_to(
+            view,
+            self.target(),
+            self.transition_duration,
+            now,
+        );
+    }
+
+    /// Go back to where the camera was before it was last moved
+    ///
+    /// The camera is animated like for [`Viewer::show_view`].
+    pub fn back(&mut self, now: Instant) {
+        if let Some(pose) = self.history.back(self.camera.pose()) {
+            self.go_to(pose, now);
+        }
+    }
+
+    /// Go forward again, to where the camera was before [`Viewer::back`]
+    pub fn forward(&mut self, now: Instant) {
+        if let Some(pose) = self.history.forward(self.camera.pose()) {
+            self.go_to(pose, now);
+        }
+    }
+
+    /// Advance any running camera animation
+    ///
+    /// Returns `true`, if the animation is still running and another frame
+    /// should be drawn.
+    pub fn advance(&mut self, now: Instant) -> bool {
+        if self.camera.advance_transition(now) {
+            self.update_planes();
+        }
+
+        self.camera.is_transitioning()
+    }
+
+    /// Handle the model being updated
+    ///
+    /// The camera is fit to the first model. For later ones, it stays where it
+    /// is, unless `fit` is set.
+    pub fn handle_model_update(&mut self, model: Arc<Model>, fit: bool) {
+        if self
+            .model
+            .as_ref()
+            .is_some_and(|current| Arc::ptr_eq(current, &model))
+        {
+            return;
+        }
+
+        self.bvh = Some(Arc::new(Bvh::new(&model.mesh)));
+
+        if self.model.replace(model).is_none() {
+            self.fit_without_recording();
+        } else if fit {
+            self.fit();
+        }
+        self.update_planes();
+    }
+
+    /// Handle an input event
+    pub fn handle_input_event(&mut self, event: InputEvent) {
+        if let Some(focus_point) = self.focus_point {
+            // Direct input takes precedence over any running animation.
+            self.camera.cancel_transition();
+
+            self.input_handler.handle_event(
+                event,
+                focus_point,
+                &mut self.camera,
+            );
+            self.update_planes();
+        }
+    }
+
+    /// Compute and store a focus point, unless one is already stored
+    pub fn add_focus_point(&mut self) {
+        if let (Some(model), Some(bvh)) = (&self.model, self.bvh.as_deref()) {
+            if self.focus_point.is_none() {
+                self.focus_point =
+                    Some(self.camera.focus_point(self.cursor, model, bvh));
+                self.pose_before_interaction = Some(self.camera.pose());
+            }
+        }
+    }
+
+    /// Remove the stored focus point
+    pub fn remove_focus_point(&mut self) {
+        self.focus_point = None;
+
+        if let Some(pose) = self.pose_before_interaction.take() {
+            if pose != self.camera.pose() {
+                self.history.record(pose);
+            }
+        }
+    }
+
+    fn go_to(&mut self, pose: CameraPose, now: Instant) {
+        self.camera.transition_to_pose(
+            pose,
+            self.target(),
+            self.transition_duration,
+            now,
+        );
+        self.update_planes();
+    }
+
+    /// The point that views are centered on
+    fn target(&self) -> Point<3> {
+        self.model
+            .as_ref()
+            .map(|model| model.aabb.center())
+            .unwrap_or_else(Point::origin)
+    }
+
+    fn update_planes(&mut self) {
+        if let Some(model) = &self.model {
+            self.camera.update_planes(&model.aabb);
+        }
+    }
+}
+
+impl Default for Viewer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
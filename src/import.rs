@@ -0,0 +1,501 @@
+//! Importing meshes from files
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek};
+use std::path::Path;
+
+use fj_interop::{Color, Mesh};
+use fj_math::{Aabb, Point, Triangle};
+
+/// Import a mesh from a file
+///
+/// The format is chosen by the case-insensitive extension of the file. STL,
+/// OBJ and 3MF files are supported.
+pub fn import(path: &Path) -> Result<fj_interop::Model, Error> {
+    let extension = path
+        .extension()
+        .ok_or(Error::NoExtension)?
+        .to_string_lossy()
+        .to_ascii_lowercase();
+    let file = File::open(path)?;
+
+    let mesh = match extension.as_str() {
+        "stl" => import_stl(BufReader::new(file))?,
+        "obj" => import_obj(BufReader::new(file))?,
+        "3mf" => import_3mf(BufReader::new(file))?,
+        _ => return Err(Error::InvalidExtension(extension)),
+    };
+
+    to_model(mesh)
+}
+
+/// Import a mesh from an STL file, in either the ASCII or the binary format
+pub fn import_stl(mut read: impl Read) -> Result<Mesh<Point<3>>, Error> {
+    let mut bytes = Vec::new();
+    read.read_to_end(&mut bytes)?;
+
+    // Binary files can start with "solid" too, so their size is checked
+    // first. It is fully determined by the number of triangles.
+    let triangles = match bytes.get(80..84) {
+        Some(&[a, b, c, d])
+            if bytes.len() as u64
+                == 84 + 50 * u64::from(u32::from_le_bytes([a, b, c, d])) =>
+        {
+            binary_stl_triangles(&bytes[84..])
+        }
+        _ => {
+            let text =
+                std::str::from_utf8(&bytes).map_err(|_| Error::InvalidStl)?;
+            if !text.trim_start().starts_with("solid") {
+                return Err(Error::InvalidStl);
+            }
+
+            ascii_stl_triangles(text)?
+        }
+    };
+
+    let mut mesh = Mesh::new();
+    for triangle in triangles {
+        push_triangle(&mut mesh, triangle);
+    }
+
+    Ok(mesh)
+}
+
+/// Import a mesh from an OBJ file
+///
+/// All objects in the file are merged into a single mesh. Materials are
+/// ignored.
+pub fn import_obj(mut read: impl BufRead) -> Result<Mesh<Point<3>>, Error> {
+    let (models, _) = tobj::load_obj_buf(
+        &mut read,
+        &tobj::LoadOptions {
+            triangulate: true,
+            ..Default::default()
+        },
+        |_| Err(tobj::LoadError::OpenFileFailed),
+    )?;
+
+    let mut mesh = Mesh::new();
+    for model in models {
+        let positions = &model.mesh.positions;
+        let point = |index: u32| {
+            let i = index as usize * 3;
+            positions
+                .get(i..i + 3)
+                .map(|p| [p[0], p[1], p[2]].map(f64::from))
+                .ok_or(Error::IndexOutOfBounds)
+        };
+
+        for indices in model.mesh.indices.chunks_exact(3) {
+            let triangle =
+                [point(indices[0])?, point(indices[1])?, point(indices[2])?];
+            push_triangle(&mut mesh, triangle);
+        }
+    }
+
+    Ok(mesh)
+}
+
+/// Import a mesh from a 3MF file
+///
+/// All build items in the file are merged into a single mesh, with their
+/// transforms applied.
+pub fn import_3mf(read: impl Read + Seek) -> Result<Mesh<Point<3>>, Error> {
+    let mut mesh = Mesh::new();
+
+    for model in threemf::read(read)? {
+        for item in &model.build.item {
+            push_3mf_object(
+                &mut mesh,
+                &model.resources.object,
+                item.objectid,
+                item.transform.unwrap_or(IDENTITY_3MF),
+                0,
+            )?;
+        }
+    }
+
+    Ok(mesh)
+}
+
+/// An error importing a mesh
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The file has no extension
+    #[error("no extension specified")]
+    NoExtension,
+
+    /// The extension of the file is not one of a supported format
+    #[error("unrecognized extension `{0}`")]
+    InvalidExtension(String),
+
+    /// The file could not be read
+    #[error("I/O error whilst importing file")]
+    Io(#[from] io::Error),
+
+    /// The file is neither a valid ASCII, nor a valid binary STL file
+    #[error("invalid STL file")]
+    InvalidStl,
+
+    /// The OBJ file could not be parsed
+    #[error("error whilst importing OBJ file")]
+    Obj(#[from] tobj::LoadError),
+
+    /// The 3MF file could not be parsed
+    #[error("error whilst importing 3MF file")]
+    ThreeMf(#[from] threemf::Error),
+
+    /// A triangle or 3MF component refers to something that doesn't exist
+    #[error("reference to a vertex or object that doesn't exist")]
+    IndexOutOfBounds,
+
+    /// 3MF components are nested too deeply, or contain themselves
+    #[error("3MF components are nested too deeply")]
+    NestingTooDeep,
+
+    /// The file doesn't contain any triangles
+    #[error("file contains no triangles")]
+    Empty,
+}
+
+/// The transform of a 3MF item or component without a transform attribute
+const IDENTITY_3MF: [f64; 12] =
+    [1., 0., 0., 0., 1., 0., 0., 0., 1., 0., 0., 0.];
+
+fn to_model(mesh: Mesh<Point<3>>) -> Result<fj_interop::Model, Error> {
+    if mesh.triangles().next().is_none() {
+        return Err(Error::Empty);
+    }
+
+    let aabb = Aabb::<3>::from_points(mesh.vertices());
+    Ok(fj_interop::Model { mesh, aabb })
+}
+
+/// Add a triangle to the mesh, unless it is degenerate
+///
+/// Meshes from other tools often contain triangles without any area, which
+/// can't be rendered with a meaningful normal.
+fn push_triangle(mesh: &mut Mesh<Point<3>>, points: [[f64; 3]; 3]) {
+    if let Ok(triangle) = Triangle::from_points(points) {
+        mesh.push_triangle(triangle, Color::default());
+    }
+}
+
+fn binary_stl_triangles(bytes: &[u8]) -> Vec<[[f64; 3]; 3]> {
+    let float = |bytes: &[u8]| {
+        f64::from(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+
+    // Each triangle has a normal, three vertices and a two-byte attribute.
+    // The normal is ignored, as it is computed from the vertices anyway.
+    bytes
+        .chunks_exact(50)
+        .map(|triangle| {
+            [12, 24, 36]
+                .map(|offset| [0, 4, 8].map(|i| float(&triangle[offset + i..])))
+        })
+        .collect()
+}
+
+fn ascii_stl_triangles(text: &str) -> Result<Vec<[[f64; 3]; 3]>, Error> {
+    let mut triangles = Vec::new();
+    let mut facet = Vec::new();
+
+    let mut tokens = text.split_whitespace();
+    while let Some(token) = tokens.next() {
+        match token {
+            "vertex" => {
+                let mut coordinate = || {
+                    tokens
+                        .next()
+                        .and_then(|token| token.parse::<f64>().ok())
+                        .ok_or(Error::InvalidStl)
+                };
+                facet.push([coordinate()?, coordinate()?, coordinate()?]);
+            }
+            "endfacet" => {
+                // Facets are supposed to be triangles, but some tools write
+                // larger polygons. Those are split into a fan of triangles.
+                for i in 2..facet.len() {
+                    triangles.push([facet[0], facet[i - 1], facet[i]]);
+                }
+                facet.clear();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(triangles)
+}
+
+fn push_3mf_object(
+    mesh: &mut Mesh<Point<3>>,
+    objects: &[threemf::model::Object],
+    id: usize,
+    transform: [f64; 12],
+    depth: usize,
+) -> Result<(), Error> {
+    const MAX_DEPTH: usize = 32;
+
+    if depth > MAX_DEPTH {
+        return Err(Error::NestingTooDeep);
+    }
+
+    let object = objects
+        .iter()
+        .find(|object| object.id == id)
+        .ok_or(Error::IndexOutOfBounds)?;
+
+    match &object.object {
+        threemf::model::ObjectData::Mesh(object_mesh) => {
+            let vertices = &object_mesh.vertices.vertex;
+            let point = |index: usize| {
+                vertices
+                    .get(index)
+                    .map(|v| apply_3mf_transform(&transform, [v.x, v.y, v.z]))
+                    .ok_or(Error::IndexOutOfBounds)
+            };
+
+            for triangle in &object_mesh.triangles.triangle {
+                let triangle = [
+                    point(triangle.v1)?,
+                    point(triangle.v2)?,
+                    point(triangle.v3)?,
+                ];
+                push_triangle(mesh, triangle);
+            }
+        }
+        threemf::model::ObjectData::Components { component } => {
+            for component in component {
+                // The component's transform is applied first, then the one of
+                // the object containing it.
+                let transform = combine_3mf_transforms(
+                    &component.transform.unwrap_or(IDENTITY_3MF),
+                    &transform,
+                );
+                push_3mf_object(
+                    mesh,
+                    objects,
+                    component.objectid,
+                    transform,
+                    depth + 1,
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Apply a 3MF transform to a point
+///
+/// 3MF transforms are the first three columns of a 4x4 matrix, in row-major
+/// order, that points are multiplied with as row vectors.
+fn apply_3mf_transform(m: &[f64; 12], [x, y, z]: [f64; 3]) -> [f64; 3] {
+    [0, 1, 2].map(|i| x * m[i] + y * m[3 + i] + z * m[6 + i] + m[9 + i])
+}
+
+/// Returns the transform that applies `first`, then `second`
+fn combine_3mf_transforms(first: &[f64; 12], second: &[f64; 12]) -> [f64; 12] {
+    let mut combined = [0.; 12];
+
+    for row in 0..4 {
+        // The implicit fourth column of each row is 0, except for the last
+        // row, where it is 1.
+        let w = if row == 3 { 1. } else { 0. };
+
+        for column in 0..3 {
+            combined[row * 3 + column] = (0..3)
+                .map(|k| first[row * 3 + k] * second[k * 3 + column])
+                .sum::<f64>()
+                + w * second[9 + column];
+        }
+    }
+
+    combined
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use fj_interop::Mesh;
+    use fj_math::{Point, Scalar};
+    use threemf::model::{
+        Build, Component, Item, Model, Object, ObjectData, Resources, Triangle,
+        Triangles, Vertex, Vertices,
+    };
+
+    use super::{import_3mf, import_stl, IDENTITY_3MF};
+
+    const TRIANGLES: [[[f64; 3]; 3]; 2] = [
+        [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+        [[0., 0., 1.], [0., 2., 1.], [3., 0., 1.]],
+    ];
+
+    #[test]
+    fn binary_stl() {
+        let bytes = binary_stl_bytes(b"binary", &TRIANGLES);
+        let mesh = import_stl(bytes.as_slice()).unwrap();
+
+        assert_eq!(triangles(&mesh), TRIANGLES);
+    }
+
+    #[test]
+    fn binary_stl_with_a_header_that_starts_with_solid() {
+        let bytes = binary_stl_bytes(b"solid exported as binary", &TRIANGLES);
+        let mesh = import_stl(bytes.as_slice()).unwrap();
+
+        assert_eq!(triangles(&mesh), TRIANGLES);
+    }
+
+    #[test]
+    fn ascii_stl() {
+        let text = "\
+solid triangles
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 1 0
+    endloop
+  endfacet
+  facet normal 0 0 -1
+    outer loop
+      vertex 0 0 1
+      vertex 0 2 1
+      vertex 3e0 0 1
+    endloop
+  endfacet
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 2
+      vertex 1 0 2
+      vertex 1 1 2
+      vertex 0 1 2
+    endloop
+  endfacet
+endsolid triangles
+";
+        let mesh = import_stl(text.as_bytes()).unwrap();
+
+        // The quadrilateral facet is split into two triangles.
+        let mut expected = TRIANGLES.to_vec();
+        expected.push([[0., 0., 2.], [1., 0., 2.], [1., 1., 2.]]);
+        expected.push([[0., 0., 2.], [1., 1., 2.], [0., 1., 2.]]);
+        assert_eq!(triangles(&mesh), expected);
+    }
+
+    #[test]
+    fn stl_that_is_neither_ascii_nor_binary() {
+        assert!(import_stl(&b"not an STL file"[..]).is_err());
+    }
+
+    #[test]
+    fn threemf_with_nested_components() {
+        // A triangle, in a component that moves it along x, in a component
+        // that scales it by 2. The build item moves the result along z.
+        let translate =
+            |[x, y, z]: [f64; 3]| [1., 0., 0., 0., 1., 0., 0., 0., 1., x, y, z];
+        let scale = |s: f64| [s, 0., 0., 0., s, 0., 0., 0., s, 0., 0., 0.];
+        let components = |objectid, transform| ObjectData::Components {
+            component: vec![Component {
+                objectid,
+                transform: Some(transform),
+            }],
+        };
+
+        let mesh = threemf::model::Mesh {
+            vertices: Vertices {
+                vertex: TRIANGLES[0]
+                    .iter()
+                    .map(|&[x, y, z]| Vertex { x, y, z })
+                    .collect(),
+            },
+            triangles: Triangles {
+                triangle: vec![Triangle {
+                    v1: 0,
+                    v2: 1,
+                    v3: 2,
+                }],
+            },
+        };
+        let model = Model {
+            resources: Resources {
+                object: vec![
+                    object(1, ObjectData::Mesh(mesh)),
+                    object(2, components(1, translate([10., 0., 0.]))),
+                    object(3, components(2, scale(2.))),
+                    // The same triangle once more, without a transform
+                    object(4, components(1, IDENTITY_3MF)),
+                ],
+                basematerials: None,
+            },
+            build: Build {
+                item: vec![
+                    Item {
+                        objectid: 3,
+                        transform: Some(translate([0., 0., 5.])),
+                        partnumber: None,
+                    },
+                    Item {
+                        objectid: 4,
+                        transform: None,
+                        partnumber: None,
+                    },
+                ],
+            },
+            ..Model::default()
+        };
+
+        let mut bytes = Cursor::new(Vec::new());
+        threemf::write(&mut bytes, model).unwrap();
+        bytes.set_position(0);
+        let mesh = import_3mf(bytes).unwrap();
+
+        assert_eq!(
+            triangles(&mesh),
+            [[[20., 0., 5.], [22., 0., 5.], [20., 2., 5.]], TRIANGLES[0],]
+        );
+    }
+
+    fn binary_stl_bytes(header: &[u8], triangles: &[[[f64; 3]; 3]]) -> Vec<u8> {
+        let mut bytes = header.to_vec();
+        bytes.resize(80, 0);
+        bytes.extend((triangles.len() as u32).to_le_bytes());
+
+        for triangle in triangles {
+            // The normal, which is ignored
+            bytes.extend([0.; 3].map(f32::to_le_bytes).concat());
+            for point in triangle {
+                bytes.extend(point.map(|c| (c as f32).to_le_bytes()).concat());
+            }
+            // The attribute byte count
+            bytes.extend([0; 2]);
+        }
+
+        bytes
+    }
+
+    fn object(id: usize, object: ObjectData) -> Object {
+        Object {
+            id,
+            partnumber: None,
+            name: None,
+            pid: None,
+            object,
+        }
+    }
+
+    fn triangles(mesh: &Mesh<Point<3>>) -> Vec<[[f64; 3]; 3]> {
+        mesh.triangles()
+            .map(|triangle| {
+                triangle
+                    .inner
+                    .points()
+                    .map(|point| point.coords.components.map(Scalar::into_f64))
+            })
+            .collect()
+    }
+}
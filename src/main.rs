@@ -1,21 +1,40 @@
-// mod model;
-mod model1;
+mod model;
 
+mod error;
+mod export;
 mod fjviewer;
-use fj_core::algorithms::approx::Tolerance;
-use fj_core::algorithms::bounding_volume::BoundingVolume;
-use fj_core::algorithms::triangulate::Triangulate;
-use fj_core::objects::{Region, Sketch};
+mod import;
+mod parametric;
+use std::mem;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use fj_core::objects::{Region, Sketch, Solid};
 use fj_core::operations::build::{BuildRegion, BuildSketch};
 use fj_core::operations::sweep::SweepSketch;
 use fj_core::operations::update::UpdateSketch;
-use fj_math::{Aabb, Point, Scalar};
+use fj_core::Core;
+use fj_math::{Point, Scalar, Vector};
 
-use iced::widget::{center, column, shader};
-use iced::Length::{self, Fill};
-use iced::{Center, Element};
+use iced::widget::{
+    button, center, checkbox, column, container, radio, row, shader, slider,
+    stack, text, text_input,
+};
+use iced::{Length, Padding};
+use iced::{Center, Element, Task};
 
-use model1::Program;
+use error::error_chain;
+use export::Format;
+use fjviewer::graphics::{
+    Background, DrawConfig, Light, LightSpace, Lighting, Material,
+};
+use fjviewer::{Axis, FieldOfView, Projection, RotationMode, StandardView};
+use model::{Command, Commands, Program, Projector, Visibility};
+use parametric::{
+    BuildError, PanelMessage, Parameter, ParameterPanel, Parameters,
+    ParametricModel,
+};
 
 fn main() -> iced::Result {
     iced::application(
@@ -23,28 +42,681 @@ fn main() -> iced::Result {
         App::update,
         App::view,
     )
-    .run()
+    .run_with(App::new)
 }
 
-struct App;
+struct App {
+    definition: Arc<dyn ParametricModel>,
+    parameters: ParameterPanel,
+
+    /// The most recently built model, if any has been built yet
+    model: Option<Arc<fj_interop::Model>>,
+
+    /// Counts the builds that were started, so results of outdated builds can
+    /// be dropped
+    generation: u64,
+    building: bool,
+
+    /// Why the last build failed, if it did
+    error: Option<BuildError>,
+
+    /// The path that models are exported to, without an extension
+    export_path: String,
+
+    /// The outcome of the last export
+    export_result: Option<Result<PathBuf, String>>,
+
+    /// The path of the mesh file to import
+    import_path: String,
+
+    /// The imported mesh, or why importing it failed
+    imported: Option<Result<Arc<fj_interop::Model>, String>>,
+    fit_new_model: bool,
+    projection: Projection,
+    rotation_mode: RotationMode,
+    field_of_view: f64,
+    vertical_field_of_view: bool,
+    dolly_zoom: bool,
+
+    /// How long the camera takes to move to another view, in milliseconds
+    transition_duration: f64,
+    projector: Option<Projector>,
+    sample_count: u32,
+    wireframe: bool,
+    edges: bool,
+    crease_angle: f64,
+    lighting: LightingPreset,
+    material: Material,
+    background: Background,
+    second_viewer: bool,
+
+    /// The navigation commands sent to the main viewer
+    commands: Commands,
+}
 
 #[derive(Debug, Clone)]
-enum Message {}
+enum Message {
+    Parameter(PanelMessage),
+    ModelBuilt(u64, Result<Arc<fj_interop::Model>, BuildError>),
+    ExportPathChanged(String),
+    Export(Format),
+    Exported(Result<PathBuf, String>),
+    ImportPathChanged(String),
+    Import,
+    Imported(Result<Arc<fj_interop::Model>, String>),
+    FitNewModelToggled(bool),
+    OrthographicToggled(bool),
+    RotationModeSelected(RotationMode),
+    FieldOfViewChanged(f64),
+    VerticalFieldOfViewToggled(bool),
+    DollyZoomToggled(bool),
+    TransitionDurationChanged(f64),
+    ViewChanged(Box<Projector>),
+    SampleCountSelected(u32),
+    WireframeToggled(bool),
+    EdgesToggled(bool),
+    CreaseAngleChanged(f64),
+    LightingSelected(LightingPreset),
+    MetallicChanged(f32),
+    RoughnessChanged(f32),
+    BackgroundSelected(Background),
+    SecondViewerToggled(bool),
+    Command(Command),
+}
 
 impl App {
-    fn new() -> Self {
-        Self {}
+    fn new() -> (Self, Task<Message>) {
+        let definition = Arc::new(Cuboid);
+
+        let mut app = Self {
+            parameters: ParameterPanel::new(definition.parameters()),
+            definition,
+            model: None,
+            generation: 0,
+            building: false,
+            error: None,
+            export_path: String::from("model"),
+            export_result: None,
+            import_path: String::new(),
+            imported: None,
+            fit_new_model: false,
+            projection: Projection::default(),
+            rotation_mode: RotationMode::default(),
+            field_of_view: 90.,
+            vertical_field_of_view: false,
+            dolly_zoom: false,
+            transition_duration: 300.,
+            projector: None,
+            sample_count: 4,
+            wireframe: false,
+            edges: true,
+            crease_angle: 30.,
+            lighting: LightingPreset::Headlight,
+            material: Material::default(),
+            background: Background::default(),
+            second_viewer: false,
+            commands: Commands::default(),
+        };
+        let task = app.rebuild_model();
+
+        (app, task)
     }
 
-    fn update(&mut self, message: Message) {
+    /// Start building the model from the current parameters
+    ///
+    /// Building and triangulating the model can take a while, so it happens in
+    /// the background. The current model stays visible until the new one is
+    /// ready.
+    fn rebuild_model(&mut self) -> Task<Message> {
+        self.generation += 1;
+        self.building = true;
+
+        let generation = self.generation;
+        let definition = Arc::clone(&self.definition);
+        let parameters = self.parameters.values().clone();
+        Task::perform(
+            async move { parametric::build(&*definition, &parameters) },
+            move |model| Message::ModelBuilt(generation, model.map(Arc::new)),
+        )
+    }
+
+    fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Parameter(message) => {
+                if self.parameters.update(message) {
+                    return self.rebuild_model();
+                }
+            }
+            Message::ModelBuilt(generation, model) => {
+                if generation == self.generation {
+                    // If the new model is broken, keep showing the last one
+                    // that could be built.
+                    match model {
+                        Ok(model) => {
+                            self.model = Some(model);
+                            self.error = None;
+                        }
+                        Err(error) => self.error = Some(error),
+                    }
+                    self.building = false;
+                }
+            }
+            Message::ExportPathChanged(export_path) => {
+                self.export_path = export_path;
+            }
+            Message::Export(format) => {
+                let Some(model) = self.model.clone() else {
+                    return Task::none();
+                };
+                let path = PathBuf::from(&self.export_path)
+                    .with_extension(format.extension());
+
+                return Task::perform(
+                    async move {
+                        export::export(&model, format, &path)
+                            .map(|()| path)
+                            .map_err(|error| error_chain(&error))
+                    },
+                    Message::Exported,
+                );
+            }
+            Message::Exported(result) => {
+                self.export_result = Some(result);
+            }
+            Message::ImportPathChanged(import_path) => {
+                self.import_path = import_path;
+            }
+            Message::Import => {
+                let path = PathBuf::from(&self.import_path);
+
+                return Task::perform(
+                    async move {
+                        import::import(&path)
+                            .map(Arc::new)
+                            .map_err(|error| error_chain(&error))
+                    },
+                    Message::Imported,
+                );
+            }
+            Message::Imported(imported) => {
+                self.imported = Some(imported);
+            }
+            Message::FitNewModelToggled(fit_new_model) => {
+                self.fit_new_model = fit_new_model;
+            }
+            Message::OrthographicToggled(orthographic) => {
+                self.projection = if orthographic {
+                    Projection::Orthographic
+                } else {
+                    Projection::Perspective
+                };
+            }
+            Message::RotationModeSelected(rotation_mode) => {
+                self.rotation_mode = rotation_mode;
+            }
+            Message::FieldOfViewChanged(field_of_view) => {
+                self.field_of_view = field_of_view;
+            }
+            Message::VerticalFieldOfViewToggled(vertical) => {
+                self.vertical_field_of_view = vertical;
+            }
+            Message::DollyZoomToggled(dolly_zoom) => {
+                self.dolly_zoom = dolly_zoom;
+            }
+            Message::TransitionDurationChanged(transition_duration) => {
+                self.transition_duration = transition_duration;
+            }
+            Message::ViewChanged(projector) => {
+                self.projector = Some(*projector);
+            }
+            Message::SampleCountSelected(sample_count) => {
+                self.sample_count = sample_count;
+            }
+            Message::WireframeToggled(wireframe) => {
+                self.wireframe = wireframe;
+            }
+            Message::EdgesToggled(edges) => {
+                self.edges = edges;
+            }
+            Message::CreaseAngleChanged(crease_angle) => {
+                self.crease_angle = crease_angle;
+            }
+            Message::LightingSelected(lighting) => {
+                self.lighting = lighting;
+            }
+            Message::MetallicChanged(metallic) => {
+                self.material.metallic = metallic;
+            }
+            Message::RoughnessChanged(roughness) => {
+                self.material.roughness = roughness;
+            }
+            Message::BackgroundSelected(background) => {
+                self.background = background;
+            }
+            Message::SecondViewerToggled(second_viewer) => {
+                self.second_viewer = second_viewer;
+            }
+            Message::Command(command) => {
+                self.commands.send(command);
+            }
+        }
+
+        Task::none()
     }
 
     fn view(&self) -> Element<'_, Message> {
-        let [x, y, z] = [3.0, 2.0, 1.0];
-        let mut core = fj_core::Core::new();
+        let rotation_modes = [
+            ("Trackball", RotationMode::Trackball),
+            ("Turntable", RotationMode::Turntable),
+            ("Orbit X", RotationMode::Orbit(Axis::X)),
+            ("Orbit Y", RotationMode::Orbit(Axis::Y)),
+            ("Orbit Z", RotationMode::Orbit(Axis::Z)),
+        ]
+        .map(|(label, rotation_mode)| {
+            radio(
+                label,
+                rotation_mode,
+                Some(self.rotation_mode),
+                Message::RotationModeSelected,
+            )
+            .into()
+        });
+        let field_of_view = if self.vertical_field_of_view {
+            FieldOfView::Vertical(self.field_of_view.to_radians())
+        } else {
+            FieldOfView::Horizontal(self.field_of_view.to_radians())
+        };
+        let lightings = LightingPreset::ALL.map(|lighting| {
+            radio(
+                lighting.name(),
+                lighting,
+                Some(self.lighting),
+                Message::LightingSelected,
+            )
+            .into()
+        });
+
+        let backgrounds = [
+            ("Solid", Background::Solid([0.8; 3])),
+            ("Gradient", Background::default()),
+            ("Environment", Background::Environment),
+        ];
+        let selected_background =
+            backgrounds.iter().position(|(_, background)| {
+                mem::discriminant(background)
+                    == mem::discriminant(&self.background)
+            });
+        let backgrounds =
+            backgrounds.iter().enumerate().map(|(i, (label, _))| {
+                radio(*label, i, selected_background, move |i| {
+                    Message::BackgroundSelected(backgrounds[i].1)
+                })
+                .into()
+            });
+
+        // The viewer renders with the sample counts that every GPU supports.
+        // Others would be rounded up, so they aren't offered.
+        let sample_counts = [1, 4].map(|sample_count| {
+            radio(
+                format!("{sample_count}x"),
+                sample_count,
+                Some(self.sample_count),
+                Message::SampleCountSelected,
+            )
+            .into()
+        });
+        // Label the points of interest of the model, while they can be seen.
+        let labels = match &self.projector {
+            Some(projector) => labels(
+                &self.definition.labels(self.parameters.values()),
+                projector,
+            ),
+            None => Vec::new(),
+        };
+        // Mark where the validation errors of the last build are, on top of
+        // the last valid model.
+        let error_markers = match (&self.error, &self.projector) {
+            (Some(error), Some(projector)) => error_markers(error, projector),
+            _ => Vec::new(),
+        };
+        let draw_config = DrawConfig {
+            draw_mesh: self.wireframe,
+            draw_edges: self.edges,
+            crease_angle: self.crease_angle.to_radians(),
+            lighting: self.lighting.lighting(),
+            material: self.material,
+            background: self.background,
+            ..DrawConfig::default()
+        };
+        let viewers: Element<'_, Message> = match &self.model {
+            Some(model) => {
+                // A second viewer has its own camera and GPU state, so it can
+                // be moved independently of the first one.
+                let second_viewer = self.second_viewer.then(|| {
+                    shader(
+                        Program::new(Arc::clone(model))
+                            .sample_count(self.sample_count)
+                            .draw_config(draw_config.clone()),
+                    )
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                });
+
+                let program = Program::new(Arc::clone(model))
+                    .fit_new_model(self.fit_new_model)
+                    .projection(self.projection)
+                    .rotation_mode(self.rotation_mode)
+                    .field_of_view(field_of_view)
+                    .dolly_zoom(self.dolly_zoom)
+                    .transition_duration(Duration::from_secs_f64(
+                        self.transition_duration / 1000.,
+                    ))
+                    .sample_count(self.sample_count)
+                    .draw_config(draw_config.clone())
+                    .commands(self.commands)
+                    .on_view_change(|projector| {
+                        Message::ViewChanged(Box::new(projector))
+                    });
+                let viewer =
+                    shader(program).width(Length::Fill).height(Length::Fill);
+
+                row![stack![viewer].extend(labels).extend(error_markers)]
+                    .push_maybe(second_viewer)
+                    .spacing(10)
+                    .into()
+            }
+            None if self.building => center(text("Building model...")).into(),
+            None => center(text("No model")).into(),
+        };
+        // Imported meshes are shown next to the model, in a viewer of their
+        // own. They can be of any size, so the camera is fit to each new one.
+        let imported = match &self.imported {
+            Some(Ok(model)) => Some(
+                shader(
+                    Program::new(Arc::clone(model))
+                        .fit_new_model(true)
+                        .sample_count(self.sample_count)
+                        .draw_config(draw_config),
+                )
+                .width(Length::Fill)
+                .height(Length::Fill),
+            ),
+            _ => None,
+        };
+        let parameters = column![
+            self.parameters.view().map(Message::Parameter),
+            checkbox("Fit new models", self.fit_new_model)
+                .on_toggle(Message::FitNewModelToggled),
+        ]
+        .push_maybe(self.building.then(|| text("Building...")))
+        .push_maybe(self.error.as_ref().map(error_panel))
+        .push(self.export_panel())
+        .push(self.import_panel())
+        .spacing(10)
+        .width(Length::Shrink);
+        center(column![
+            "Text1",
+            "Text2",
+            self.toolbar(),
+            row![viewers].push_maybe(imported).push(parameters).spacing(10),
+            "Text3",
+            checkbox("Orthographic", self.projection == Projection::Orthographic)
+                .on_toggle(Message::OrthographicToggled),
+            row(rotation_modes).spacing(10),
+            row![
+                text(format!("Field of view: {:.0}°", self.field_of_view)),
+                slider(10.0..=150.0, self.field_of_view, Message::FieldOfViewChanged).width(200),
+                checkbox("Vertical", self.vertical_field_of_view)
+                    .on_toggle(Message::VerticalFieldOfViewToggled),
+                checkbox("Dolly zoom", self.dolly_zoom)
+                    .on_toggle(Message::DollyZoomToggled),
+            ].spacing(10),
+            row![text("Anti-aliasing:"), row(sample_counts).spacing(10)].spacing(10),
+            checkbox("Wireframe", self.wireframe)
+                .on_toggle(Message::WireframeToggled),
+            row![
+                checkbox("Edges", self.edges).on_toggle(Message::EdgesToggled),
+                text(format!("Crease angle: {:.0}°", self.crease_angle)),
+                slider(1.0..=90.0, self.crease_angle, Message::CreaseAngleChanged)
+                    .width(200),
+            ]
+            .spacing(10),
+            row![text("Lighting:"), row(lightings).spacing(10)].spacing(10),
+            row![
+                text(format!("Metallic: {:.2}", self.material.metallic)),
+                slider(0.0..=1.0, self.material.metallic, Message::MetallicChanged)
+                    .step(0.01)
+                    .width(150),
+                text(format!("Roughness: {:.2}", self.material.roughness)),
+                slider(0.0..=1.0, self.material.roughness, Message::RoughnessChanged)
+                    .step(0.01)
+                    .width(150),
+            ]
+            .spacing(10),
+            row![text("Background:"), row(backgrounds).spacing(10)].spacing(10),
+            checkbox("Second viewer", self.second_viewer)
+                .on_toggle(Message::SecondViewerToggled),].align_x(Center)).into()
+    }
+
+    fn toolbar(&self) -> Element<'_, Message> {
+        let views = [
+            ("Front", StandardView::Front),
+            ("Back", StandardView::Back),
+            ("Left", StandardView::Left),
+            ("Right", StandardView::Right),
+            ("Top", StandardView::Top),
+            ("Bottom", StandardView::Bottom),
+            ("Isometric", StandardView::Isometric),
+        ]
+        .map(|(label, view)| {
+            button(label)
+                .on_press(Message::Command(Command::ShowView(view)))
+                .into()
+        });
+
+        row![
+            button("Fit").on_press(Message::Command(Command::Fit)),
+            button("Back").on_press(Message::Command(Command::Back)),
+            button("Forward").on_press(Message::Command(Command::Forward)),
+            text("View:"),
+            row(views).spacing(5),
+            text(format!("Transition: {:.0} ms", self.transition_duration)),
+            slider(
+                0.0..=1000.0,
+                self.transition_duration,
+                Message::TransitionDurationChanged,
+            )
+            .step(50.)
+            .width(150),
+        ]
+        .spacing(10)
+        .align_y(Center)
+        .into()
+    }
+
+    fn import_panel(&self) -> Element<'_, Message> {
+        let error = match &self.imported {
+            Some(Err(error)) => Some(text(error).size(12).style(text::danger)),
+            _ => None,
+        };
+
+        column![
+            text("Import"),
+            row![
+                text_input("STL, OBJ or 3MF file", &self.import_path)
+                    .on_input(Message::ImportPathChanged)
+                    .on_submit(Message::Import),
+                button("Open").on_press(Message::Import),
+            ]
+            .spacing(10)
+            .width(320),
+        ]
+        .push_maybe(error)
+        .spacing(5)
+        .into()
+    }
+
+    fn export_panel(&self) -> Element<'_, Message> {
+        let buttons = Format::ALL.map(|format| {
+            button(format.name())
+                .on_press_maybe(
+                    self.model.is_some().then_some(Message::Export(format)),
+                )
+                .into()
+        });
+        let result = self.export_result.as_ref().map(|result| match result {
+            Ok(path) => {
+                text(format!("Exported to {}", path.display())).size(12)
+            }
+            Err(error) => text(error).size(12).style(text::danger),
+        });
+
+        column![
+            text("Export"),
+            text_input("Path", &self.export_path)
+                .on_input(Message::ExportPathChanged)
+                .width(320),
+            row(buttons).spacing(10),
+        ]
+        .push_maybe(result)
+        .spacing(5)
+        .into()
+    }
+}
+
+/// List the problems that prevented the model from being built
+fn error_panel(error: &BuildError) -> Element<'_, Message> {
+    let details = error
+        .details()
+        .iter()
+        .map(|detail| text(detail).size(12).style(text::danger).into());
+
+    container(
+        column![text(error.to_string()).style(text::danger)]
+            .extend(details)
+            .spacing(5),
+    )
+    .width(320)
+    .into()
+}
+
+/// Mark the positions that the problems refer to, in a viewer
+fn error_markers<'a>(
+    error: &BuildError,
+    projector: &Projector,
+) -> Vec<Element<'a, Message>> {
+    error
+        .points()
+        .iter()
+        .filter_map(|&point| projector.project(point))
+        .filter(|point| point.visibility != Visibility::OutsideView)
+        .map(|point| {
+            // Center the marker on the point.
+            let top = (point.position.y - 8.).max(0.);
+            let left = (point.position.x - 4.).max(0.);
+
+            container(text("\u{25cf}").size(12).style(text::danger))
+                .padding(Padding::ZERO.top(top).left(left))
+                .into()
+        })
+        .collect()
+}
+
+/// Labels for the given points of the model, with their depth in the view
+fn labels<'a>(
+    labels: &[(&'static str, Point<3>)],
+    projector: &Projector,
+) -> Vec<Element<'a, Message>> {
+    labels
+        .iter()
+        .filter_map(|&(name, point)| {
+            projector.project(point).map(|point| (name, point))
+        })
+        .filter(|(_, point)| point.visibility == Visibility::Visible)
+        .map(|(name, point)| {
+            let position = point.position;
+            container(text(format!("{name} ({:.2})", point.depth)).size(12))
+                .padding(Padding::ZERO.top(position.y).left(position.x))
+                .into()
+        })
+        .collect()
+}
+
+/// The lighting presets that can be selected in the UI
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum LightingPreset {
+    Headlight,
+    Studio,
+    Colored,
+    Environment,
+}
+
+impl LightingPreset {
+    const ALL: [Self; 4] = [
+        Self::Headlight,
+        Self::Studio,
+        Self::Colored,
+        Self::Environment,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Headlight => "Headlight",
+            Self::Studio => "Studio",
+            Self::Colored => "Colored",
+            Self::Environment => "Environment",
+        }
+    }
+
+    fn lighting(self) -> Lighting {
+        match self {
+            Self::Headlight => Lighting::headlight(),
+            Self::Studio => Lighting::studio(),
+            // A warm and a cold light from either side, plus a weak headlight,
+            // so the sides of the model facing away from them aren't black
+            Self::Colored => Lighting {
+                ambient: [0.1; 3],
+                lights: vec![
+                    Light::directional(
+                        [-1., -1., 1.],
+                        [0.8, 0.4, 0.2],
+                        LightSpace::World,
+                    ),
+                    Light::directional(
+                        [1., -1., 1.],
+                        [0.2, 0.4, 0.8],
+                        LightSpace::World,
+                    ),
+                    Light::point([0., 0., 0.], [0.2; 3], LightSpace::Camera),
+                ],
+                ..Lighting::studio()
+            },
+            Self::Environment => Lighting::environment(),
+        }
+    }
+}
+
+/// A box, centered on the origin in x and y, that extends downwards from the
+/// xy plane
+struct Cuboid;
+
+impl ParametricModel for Cuboid {
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![
+            Parameter::new("x", 3.0, 0.1..=10.0).unit("mm"),
+            Parameter::new("y", 2.0, 0.1..=10.0).unit("mm"),
+            Parameter::new("z", 1.0, 0.1..=10.0).unit("mm"),
+        ]
+    }
+
+    fn build(&self, parameters: &Parameters, core: &mut Core) -> Solid {
+        let [x, y, z] = ["x", "y", "z"].map(|name| parameters.get(name));
+
         let bottom_surface = core.layers.objects.surfaces.xy_plane();
-        let sweep_path = fj_math::Vector::from([fj_math::Scalar::ZERO, fj_math::Scalar::ZERO, (-z).into()]);
-        let model = Sketch::empty()
+        let sweep_path =
+            Vector::from([Scalar::ZERO, Scalar::ZERO, (-z).into()]);
+
+        Sketch::empty()
             .add_regions(
                 [Region::polygon(
                     [
@@ -53,43 +725,15 @@ impl App {
                         [x / 2., y / 2.],
                         [-x / 2., y / 2.],
                     ],
-                    &mut core,
+                    core,
                 )],
-                &mut core,
+                core,
             )
-            .sweep_sketch(bottom_surface, sweep_path, &mut core);
-
-        core.layers
-            .validation
-            .take_errors()
-            .expect("Model is invalid");
-        let aabb = model.aabb(&core.layers.geometry).unwrap_or(Aabb {
-            min: Point::origin(),
-            max: Point::origin(),
-        });
-
-        let mut min_extent = Scalar::MAX;
-        for extent in aabb.size().components {
-            if extent > Scalar::ZERO && extent < min_extent {
-                min_extent = extent;
-            }
-        }
-
-        let tolerance = min_extent / Scalar::from_f64(1000.);
-        let tolerance = Tolerance::from_scalar(tolerance).unwrap();
-
-        let mesh = (&model, tolerance).triangulate(&mut core);
-        let m = fj_interop::Model { mesh, aabb };
-        center(column![
-            "Text1",
-            "Text2",
-            shader(Program::new(m)).width(Length::Fill).height(Length::Fill),
-            "Text3",].align_x(Center)).into()
+            .sweep_sketch(bottom_surface, sweep_path, core)
     }
-}
 
-impl Default for App {
-    fn default() -> Self {
-        Self::new()
+    fn labels(&self, parameters: &Parameters) -> Vec<(&'static str, Point<3>)> {
+        let [x, y] = ["x", "y"].map(|name| parameters.get(name));
+        vec![("Corner", Point::from([x / 2., -y / 2., 0.]))]
     }
-}
\ No newline at end of file
+}
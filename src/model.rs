@@ -1,78 +1,665 @@
 use crate::fjviewer::graphics::drawables::Drawables;
 use crate::fjviewer::graphics::geometries::Geometries;
 use crate::fjviewer::graphics::pipelines::Pipelines;
+use crate::fjviewer::graphics::environment::Environment;
+use crate::fjviewer::graphics::skybox::{Skybox, SkyboxUniforms};
 use crate::fjviewer::graphics::transform::Transform;
 use crate::fjviewer::graphics::uniforms::Uniforms;
-use crate::fjviewer::graphics::vertices::Vertices;
-use crate::fjviewer::graphics::DrawConfig;
-use crate::fjviewer::{Camera, FocusPoint, NormalizedScreenPosition};
+use crate::fjviewer::graphics::{
+    self, blit::Blit, Background, DrawConfig, DEFAULT_SAMPLE_COUNT, DEPTH_FORMAT,
+};
+use crate::fjviewer::{
+    Bvh, Camera, FieldOfView, InputEvent, NormalizedScreenPosition, Projection,
+    RotationMode, StandardView, Viewer,
+};
 
-use iced::widget::shader::wgpu;
-use iced::Size;
-use iced::{mouse, widget::shader, Rectangle};
+use iced::advanced::Shell;
+use iced::event;
+use iced::time::Instant;
+use iced::widget::shader::{self, wgpu};
+use iced::window::RedrawRequest;
+use iced::{keyboard, mouse, Point, Rectangle, Size};
 
+use std::collections::HashMap;
 use std::mem::size_of;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 use wgpu::util::DeviceExt;
 
+const ZOOM_FACTOR_LINE: f64 = 0.075;
+const ZOOM_FACTOR_PIXEL: f64 = 0.005;
+const ROTATION_SENSITIVITY: f64 = 5.;
 
-pub struct Program {
+pub struct Program<Message> {
     model: Arc<fj_interop::Model>,
+    projection: Projection,
+    rotation_mode: RotationMode,
+    field_of_view: FieldOfView,
+    dolly_zoom: bool,
+    fit_new_model: bool,
+    transition_duration: Duration,
+    draw_config: DrawConfig,
+    sample_count: u32,
+    commands: Commands,
+    on_view_change: Option<Box<dyn Fn(Projector) -> Message>>,
 }
 
-impl Program {
-    pub fn new(model: fj_interop::Model) -> Self {
-        Self { 
-            model: Arc::new(model)
+impl<Message> Program<Message> {
+    /// Creates a viewer for a model
+    ///
+    /// The model can be swapped for another one at any time, by passing it to
+    /// a new `Program`. Pass the same [`Arc`] on every call to `view`, to
+    /// avoid having the model uploaded to the GPU again.
+    pub fn new(model: impl Into<Arc<fj_interop::Model>>) -> Self {
+        Self {
+            model: model.into(),
+            projection: Projection::default(),
+            rotation_mode: RotationMode::default(),
+            field_of_view: FieldOfView::default(),
+            dolly_zoom: false,
+            fit_new_model: false,
+            transition_duration: Viewer::DEFAULT_TRANSITION_DURATION,
+            draw_config: DrawConfig::default(),
+            sample_count: DEFAULT_SAMPLE_COUNT,
+            commands: Commands::default(),
+            on_view_change: None,
         }
     }
+
+    /// Sets the projection mode of the camera
+    pub fn projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Sets how dragging with the left mouse button rotates the model
+    pub fn rotation_mode(mut self, rotation_mode: RotationMode) -> Self {
+        self.rotation_mode = rotation_mode;
+        self
+    }
+
+    /// Sets the field of view of the camera in perspective mode
+    pub fn field_of_view(mut self, field_of_view: FieldOfView) -> Self {
+        self.field_of_view = field_of_view;
+        self
+    }
+
+    /// Sets whether changes to the field of view keep the size of the model
+    /// on screen, by moving the camera
+    pub fn dolly_zoom(mut self, dolly_zoom: bool) -> Self {
+        self.dolly_zoom = dolly_zoom;
+        self
+    }
+
+    /// Sets whether the camera is moved to fit a new model into the view
+    ///
+    /// By default, the camera stays where it is when the model changes. It is
+    /// always fit to the first model.
+    pub fn fit_new_model(mut self, fit_new_model: bool) -> Self {
+        self.fit_new_model = fit_new_model;
+        self
+    }
+
+    /// Sets how long the camera takes to move to another view, like a
+    /// standard view or the previous one
+    ///
+    /// With a duration of zero, the camera jumps to the view.
+    pub fn transition_duration(mut self, duration: Duration) -> Self {
+        self.transition_duration = duration;
+        self
+    }
+
+    /// Sets what is drawn, e.g. whether a wireframe is shown on top of the
+    /// model
+    pub fn draw_config(mut self, draw_config: DrawConfig) -> Self {
+        self.draw_config = draw_config;
+        self
+    }
+
+    /// Sets the number of samples per pixel used for anti-aliasing
+    ///
+    /// Valid values are 1 (no anti-aliasing), 2, 4 and 8. Numbers that the
+    /// device doesn't support are rounded up to the next supported one, or
+    /// down to the highest supported one, if there is none. As iced creates
+    /// the device, that currently means that 2 becomes 4, and 8 becomes 4.
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Sets the commands that move the camera, like going back to the
+    /// previous view
+    ///
+    /// The viewer executes the latest command once, when it is sent with
+    /// [`Commands::send`]. Commands that were sent before the viewer was
+    /// shown are ignored.
+    pub fn commands(mut self, commands: Commands) -> Self {
+        self.commands = commands;
+        self
+    }
+
+    /// Sets the message that is produced, whenever the view changes
+    ///
+    /// The [`Projector`] maps between the model and the widget, so overlays
+    /// can be placed on top of the model.
+    pub fn on_view_change(
+        mut self,
+        on_view_change: impl Fn(Projector) -> Message + 'static,
+    ) -> Self {
+        self.on_view_change = Some(Box::new(on_view_change));
+        self
+    }
 }
 
-impl<Message> shader::Program<Message> for Program {
-    type State = ();
+/// A command that moves the camera of a viewer
+///
+/// These are the same actions that are available through keyboard shortcuts,
+/// for use by buttons or menus of the application.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Command {
+    /// Fit the model into the view
+    Fit,
+
+    /// Go back to the previous view
+    Back,
+
+    /// Go forward again, after going back
+    Forward,
+
+    /// Show one of the standard views
+    ShowView(StandardView),
+}
+
+/// The latest [`Command`] sent to a viewer
+///
+/// The application keeps this around and passes it to the viewer with
+/// [`Program::commands`]. Every call to [`Commands::send`] counts as a new
+/// command, even if it is the same as the previous one.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Commands {
+    generation: u64,
+    latest: Option<Command>,
+}
+
+impl Commands {
+    /// Send a command to the viewer
+    pub fn send(&mut self, command: Command) {
+        self.generation += 1;
+        self.latest = Some(command);
+    }
+}
+
+/// The per-widget state of a [`Program`]
+#[derive(Default)]
+pub struct State {
+    id: ViewerId,
+    viewer: Viewer,
+    held_mouse_button: Option<mouse::Button>,
+
+    /// Whether the viewer receives keyboard input
+    ///
+    /// The viewer takes focus when it's clicked, and loses it when anything
+    /// else is clicked, like a text input.
+    is_focused: bool,
+
+    /// The generation of the last [`Commands`] that were seen
+    commands: Option<u64>,
+
+    /// The vertex transform and widget size of the last view change that was
+    /// reported
+    last_view: Option<([f32; 16], Size)>,
+}
+
+/// Identifies the GPU state of a viewer widget
+///
+/// Every widget's [`State`] owns a unique one, so several viewers can be shown
+/// at the same time. The GPU state is released once the widget, and with it
+/// its id, is gone.
+#[derive(Clone, Debug)]
+struct ViewerId(Arc<u64>);
+
+impl Default for ViewerId {
+    fn default() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self(Arc::new(NEXT_ID.fetch_add(1, Ordering::Relaxed)))
+    }
+}
+
+impl State {
+    /// Execute the latest command, if it is new
+    fn handle_commands(&mut self, commands: &Commands) -> event::Status {
+        let previous = self.commands.replace(commands.generation);
+
+        match (previous, commands.latest) {
+            (Some(previous), Some(command))
+                if previous != commands.generation =>
+            {
+                self.execute(command);
+                event::Status::Captured
+            }
+            _ => event::Status::Ignored,
+        }
+    }
+
+    fn execute(&mut self, command: Command) {
+        let now = Instant::now();
+
+        match command {
+            Command::Fit => self.viewer.fit(),
+            Command::Back => self.viewer.back(now),
+            Command::Forward => self.viewer.forward(now),
+            Command::ShowView(view) => self.viewer.show_view(view, now),
+        }
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        event: mouse::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> event::Status {
+        match event {
+            mouse::Event::CursorMoved { .. } => {
+                // A scroll gesture ends, once the cursor moves.
+                if self.held_mouse_button.is_none() {
+                    self.viewer.remove_focus_point();
+                }
+
+                // Keep tracking the cursor outside of the widget while a
+                // button is held, so drags don't stop at the widget border.
+                let position = if self.held_mouse_button.is_some() {
+                    cursor.position_from(bounds.position())
+                } else {
+                    cursor.position_in(bounds)
+                };
+                let current =
+                    position.map(|position| normalize(position, bounds.size()));
+                let previous = std::mem::replace(self.viewer.cursor(), current);
+
+                let input_event =
+                    match (previous, current, self.held_mouse_button) {
+                        (Some(previous), Some(current), Some(button)) => {
+                            match button {
+                                mouse::Button::Left => {
+                                    let diff_x = current.x - previous.x;
+                                    let diff_y = current.y - previous.y;
+                                    let angle_x =
+                                        -diff_y * ROTATION_SENSITIVITY;
+                                    let angle_y = diff_x * ROTATION_SENSITIVITY;
+
+                                    Some(InputEvent::Rotation {
+                                        angle_x,
+                                        angle_y,
+                                    })
+                                }
+                                mouse::Button::Right => {
+                                    Some(InputEvent::Translation {
+                                        previous,
+                                        current,
+                                    })
+                                }
+                                _ => None,
+                            }
+                        }
+                        _ => None,
+                    };
+
+                match input_event {
+                    Some(input_event) => {
+                        self.viewer.handle_input_event(input_event);
+                        event::Status::Captured
+                    }
+                    None => event::Status::Ignored,
+                }
+            }
+            mouse::Event::ButtonPressed(
+                button @ (mouse::Button::Left | mouse::Button::Right),
+            ) if cursor.is_over(bounds) => {
+                self.held_mouse_button = Some(button);
+                self.viewer.remove_focus_point();
+                self.viewer.add_focus_point();
+                event::Status::Captured
+            }
+            mouse::Event::ButtonReleased(button)
+                if self.held_mouse_button == Some(button) =>
+            {
+                self.held_mouse_button = None;
+                self.viewer.remove_focus_point();
+                event::Status::Captured
+            }
+            mouse::Event::WheelScrolled { delta } if cursor.is_over(bounds) => {
+                let delta = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => {
+                        f64::from(y) * ZOOM_FACTOR_LINE
+                    }
+                    mouse::ScrollDelta::Pixels { y, .. } => {
+                        f64::from(y) * ZOOM_FACTOR_PIXEL
+                    }
+                };
+
+                // Keep the focus point until the cursor moves, so a series of
+                // scroll steps counts as a single change of the view.
+                self.viewer.add_focus_point();
+                self.viewer.handle_input_event(InputEvent::Zoom(delta));
+                event::Status::Captured
+            }
+            _ => event::Status::Ignored,
+        }
+    }
+
+    /// Handle keyboard shortcuts
+    ///
+    /// The standard views are laid out like on a numeric keypad: `1`, `3` and
+    /// `7` show the front, right and top views, and the opposite views with
+    /// `Ctrl` held. `0` shows the isometric view. `F` fits the model into the
+    /// view, `Shift+F` fits the triangle under the cursor into the view.
+    /// `Ctrl+Z` goes back to the previous view, `Ctrl+Shift+Z` or `Ctrl+Y`
+    /// forward again (with `Cmd` instead of `Ctrl` on macOS).
+    ///
+    /// Shortcuts are only handled while the viewer has focus.
+    fn handle_keyboard_event(
+        &mut self,
+        event: keyboard::Event,
+    ) -> event::Status {
+        let keyboard::Event::KeyPressed { key, modifiers, .. } = event else {
+            return event::Status::Ignored;
+        };
+
+        if let keyboard::Key::Character(c) = key.as_ref() {
+            if modifiers.command() && c.eq_ignore_ascii_case("z") {
+                if modifiers.shift() {
+                    self.execute(Command::Forward);
+                } else {
+                    self.execute(Command::Back);
+                }
+                return event::Status::Captured;
+            }
+            if modifiers.command() && c.eq_ignore_ascii_case("y") {
+                self.execute(Command::Forward);
+                return event::Status::Captured;
+            }
+            if c.eq_ignore_ascii_case("f") {
+                if modifiers.shift() {
+                    self.viewer.frame_hovered();
+                } else {
+                    self.execute(Command::Fit);
+                }
+                return event::Status::Captured;
+            }
+        }
+
+        let view = match (key.as_ref(), modifiers.control()) {
+            (keyboard::Key::Character("1"), false) => StandardView::Front,
+            (keyboard::Key::Character("1"), true) => StandardView::Back,
+            (keyboard::Key::Character("3"), false) => StandardView::Right,
+            (keyboard::Key::Character("3"), true) => StandardView::Left,
+            (keyboard::Key::Character("7"), false) => StandardView::Top,
+            (keyboard::Key::Character("7"), true) => StandardView::Bottom,
+            (keyboard::Key::Character("0"), _) => StandardView::Isometric,
+            _ => return event::Status::Ignored,
+        };
+
+        self.execute(Command::ShowView(view));
+        event::Status::Captured
+    }
+}
+
+impl<Message> shader::Program<Message> for Program<Message> {
+    type State = State;
 
     type Primitive = Primitive;
 
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: shader::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+        shell: &mut Shell<'_, Message>,
+    ) -> (event::Status, Option<Message>) {
+        state
+            .viewer
+            .set_aspect_ratio(f64::from(bounds.width / bounds.height));
+        state.viewer.set_projection(self.projection);
+        state.viewer.set_rotation_mode(self.rotation_mode);
+        state
+            .viewer
+            .set_transition_duration(self.transition_duration);
+        state.viewer.set_draw_config(self.draw_config.clone());
+        state
+            .viewer
+            .set_field_of_view(self.field_of_view, self.dolly_zoom);
+        // The camera is configured first, so a model is fit into the view
+        // that it is shown in.
+        state
+            .viewer
+            .handle_model_update(Arc::clone(&self.model), self.fit_new_model);
+
+        if let shader::Event::Mouse(mouse::Event::ButtonPressed(_)) = event {
+            state.is_focused = cursor.is_over(bounds);
+        }
+
+        // Commands are executed before the event is handled, so a transition
+        // they start is advanced right away.
+        let command_status = state.handle_commands(&self.commands);
+
+        let status = match event {
+            shader::Event::Mouse(event) => {
+                state.handle_mouse_event(event, bounds, cursor)
+            }
+            shader::Event::Keyboard(event) if state.is_focused => {
+                state.handle_keyboard_event(event)
+            }
+            shader::Event::RedrawRequested(now) => {
+                if state.viewer.advance(now) {
+                    shell.request_redraw(RedrawRequest::NextFrame);
+                }
+                event::Status::Ignored
+            }
+            _ => event::Status::Ignored,
+        };
+
+        if status == event::Status::Captured
+            || command_status == event::Status::Captured
+        {
+            shell.request_redraw(RedrawRequest::NextFrame);
+        }
+
+        let message = self.on_view_change.as_ref().and_then(|on_view_change| {
+            let camera = state.viewer.camera();
+            let view = (
+                Transform::for_vertices(camera, camera.aspect_ratio()).0,
+                bounds.size(),
+            );
+
+            (state.last_view.replace(view) != Some(view)).then(|| {
+                on_view_change(Projector {
+                    camera: camera.clone(),
+                    bvh: state.viewer.bvh().cloned(),
+                    size: bounds.size(),
+                })
+            })
+        });
+
+        (status, message)
+    }
+
     fn draw(
         &self,
         state: &Self::State,
-        cursor: mouse::Cursor,
-        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+        _bounds: Rectangle,
     ) -> Self::Primitive {
-        // println!("{:?}", bounds);
-        Primitive::new(Arc::clone(&self.model), bounds)
+        Primitive::new(
+            state.id.clone(),
+            Arc::clone(&self.model),
+            state.viewer.camera().clone(),
+            state.viewer.draw_config().clone(),
+            self.sample_count,
+        )
     }
 
     fn mouse_interaction(
         &self,
-        _state: &Self::State,
-        _bounds: Rectangle,
+        state: &Self::State,
+        bounds: Rectangle,
         cursor: mouse::Cursor,
     ) -> mouse::Interaction {
-        // println!("{:?}", cursor);
-        mouse::Interaction::default()
+        if state.held_mouse_button.is_some() {
+            mouse::Interaction::Grabbing
+        } else if cursor.is_over(bounds) {
+            mouse::Interaction::Grab
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+/// Convert a widget-local cursor position into normalized coordinates
+///
+/// The center of the widget is at (0, 0), and x spans from -1 to +1. y is
+/// scaled by the aspect ratio of the widget.
+fn normalize(position: Point, size: Size) -> NormalizedScreenPosition {
+    let width = f64::from(size.width);
+    let height = f64::from(size.height);
+    let aspect_ratio = width / height;
+
+    NormalizedScreenPosition {
+        x: f64::from(position.x) / width * 2. - 1.,
+        y: -(f64::from(position.y) / height * 2. - 1.) / aspect_ratio,
+    }
+}
+
+/// The inverse of [`normalize`]
+fn denormalize(position: NormalizedScreenPosition, size: Size) -> Point {
+    let width = f64::from(size.width);
+    let height = f64::from(size.height);
+    let aspect_ratio = width / height;
+
+    Point::new(
+        ((position.x + 1.) / 2. * width) as f32,
+        ((1. - position.y * aspect_ratio) / 2. * height) as f32,
+    )
+}
+
+/// Maps between model space and widget-local pixel coordinates
+///
+/// A snapshot of the camera of a viewer widget, as reported by
+/// [`Program::on_view_change`].
+#[derive(Clone, Debug)]
+pub struct Projector {
+    camera: Camera,
+    bvh: Option<Arc<Bvh>>,
+    size: Size,
+}
+
+impl Projector {
+    /// Project a point of the model into the widget
+    ///
+    /// Returns `None` for points at or behind the camera, which don't have a
+    /// position on screen.
+    pub fn project(&self, point: fj_math::Point<3>) -> Option<ScreenPoint> {
+        let (position, depth) = self.camera.model_to_screen(point)?;
+        let position = denormalize(position, self.size);
+
+        let is_in_view = Rectangle::with_size(self.size).contains(position)
+            && depth >= self.camera.near_plane()
+            && depth <= self.camera.far_plane();
+
+        let visibility = if !is_in_view {
+            Visibility::OutsideView
+        } else if self.is_occluded(point, position) {
+            Visibility::Occluded
+        } else {
+            Visibility::Visible
+        };
+
+        Some(ScreenPoint {
+            position,
+            depth,
+            visibility,
+        })
+    }
+
+    /// Compute the ray through a widget-local position, in model space
+    ///
+    /// Returns the origin and the normalized direction of the ray. See
+    /// [`Camera::cursor_ray`].
+    pub fn unproject(
+        &self,
+        position: Point,
+    ) -> (fj_math::Point<3>, fj_math::Vector<3>) {
+        self.camera.cursor_ray(normalize(position, self.size))
     }
+
+    /// Indicate whether the model hides a point that projects to `position`
+    fn is_occluded(&self, point: fj_math::Point<3>, position: Point) -> bool {
+        let Some(bvh) = &self.bvh else {
+            return false;
+        };
+
+        let (origin, direction) = self.unproject(position);
+        let distance = (point - origin).magnitude().into_f64();
+
+        // Points on the surface of the model shouldn't hide themselves.
+        let tolerance = distance * 1e-6;
+
+        bvh.cast_ray(origin, direction)
+            .is_some_and(|hit| hit.distance < distance - tolerance)
+    }
+}
+
+/// A point of the model, projected into the widget
+#[derive(Clone, Copy, Debug)]
+pub struct ScreenPoint {
+    /// The widget-local position in logical pixels
+    pub position: Point,
+
+    /// The distance from the camera along the view direction
+    pub depth: f64,
+
+    /// Whether the point can be seen
+    pub visibility: Visibility,
 }
 
-pub const SAMPLE_COUNT: u32 = 4;
+/// Whether a point of the model can be seen
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Visibility {
+    /// The point is in view, and not hidden by the model
+    Visible,
+
+    /// The point is in view, but hidden behind another part of the model
+    Occluded,
+
+    /// The point is outside of the widget, or clipped by the near or far
+    /// plane
+    OutsideView,
+}
 
 #[derive(Debug)]
 pub struct Primitive {
-    // camera: Camera,
-    // cursor: Option<NormalizedScreenPosition>,
-    // draw_config: DrawConfig,
-    // focus_point: Option<FocusPoint>,
+    id: ViewerId,
+    camera: Camera,
+    draw_config: DrawConfig,
     model: Arc<fj_interop::Model>,
+    sample_count: u32,
 }
 
 impl Primitive {
-    pub fn new(model: Arc<fj_interop::Model>, bounds: Rectangle,) -> Self {
+    fn new(
+        id: ViewerId,
+        model: Arc<fj_interop::Model>,
+        camera: Camera,
+        draw_config: DrawConfig,
+        sample_count: u32,
+    ) -> Self {
         Self {
-            // camera: Camera::default(),
-            // cursor: None,
-            // draw_config: DrawConfig::default(),
-            // focus_point: None,
+            id,
+            camera,
+            draw_config,
             model,
+            sample_count,
         }
     }
 }
@@ -85,28 +672,111 @@ impl shader::Primitive for Primitive {
         queue: &wgpu::Queue,
         format: wgpu::TextureFormat,
         storage: &mut shader::Storage,
-        _bounds: &Rectangle,
+        bounds: &Rectangle,
         viewport: &shader::Viewport,
     ) {
-        // println!("prepare...");
-        // println!("{:?}", bounds);
-        if !storage.has::<Pipeline>() {
-            storage.store(Pipeline::new(
-                device,
-                queue,
-                format,
-                viewport.physical_size(),
-                self.model.clone(),
-            ));
+        let bounds = *bounds * viewport.scale_factor() as f32;
+        let size = Size::new(
+            bounds.width.round() as u32,
+            bounds.height.round() as u32,
+        );
+
+        // Primitives don't get access to the adapter. iced creates the device
+        // without adapter-specific format features, so textures are validated
+        // against the features that every adapter guarantees.
+        let sample_count = graphics::supported_sample_count(
+            self.sample_count,
+            &format.guaranteed_format_features(device.features()),
+            &DEPTH_FORMAT.guaranteed_format_features(device.features()),
+        );
+
+        if !storage.has::<SharedEnvironment>() {
+            storage.store(SharedEnvironment::new(device));
         }
+        let environment = storage.get_mut::<SharedEnvironment>().unwrap();
+        if self.draw_config.uses_environment() {
+            environment.load(device, queue);
+        }
+        let environment = Arc::clone(&environment.current);
+
+        if !storage.has::<ViewerPipelines>() {
+            storage.store(ViewerPipelines::default());
+        }
+        let viewers = storage.get_mut::<ViewerPipelines>().unwrap();
 
-        let pipeline = storage.get_mut::<Pipeline>().unwrap();
+        // Release the GPU state of viewers that have gone away.
+        viewers.0.retain(|_, (id, _)| id.strong_count() > 0);
 
-        pipeline.update(
-            device,
-            queue,
-            viewport.physical_size(),
+        let (_, pipeline) = viewers
+            .0
+            .entry(*self.id.0)
+            .and_modify(|(_, pipeline)| {
+                if pipeline.sample_count != sample_count
+                    || !Arc::ptr_eq(&pipeline.environment, &environment)
+                {
+                    *pipeline = Pipeline::new(
+                        device,
+                        format,
+                        size,
+                        &self.model,
+                        &environment,
+                        sample_count,
+                    );
+                }
+            })
+            .or_insert_with(|| {
+                (
+                    Arc::downgrade(&self.id.0),
+                    Pipeline::new(
+                        device,
+                        format,
+                        size,
+                        &self.model,
+                        &environment,
+                        sample_count,
+                    ),
+                )
+            });
+
+        let aspect_ratio = f64::from(bounds.width / bounds.height);
+        let eye = match self.camera.projection() {
+            Projection::Perspective => {
+                let [x, y, z] = self.camera.position().coords.components;
+                [x, y, z, fj_math::Scalar::ONE]
+            }
+            Projection::Orthographic => {
+                let [x, y, z] = self
+                    .camera
+                    .camera_to_model()
+                    .inverse()
+                    .transform_vector(&fj_math::Vector::from([0., 0., 1.]))
+                    .components;
+                [x, y, z, fj_math::Scalar::ZERO]
+            }
+        };
+        let uniforms = Uniforms {
+            transform: Transform::for_vertices(&self.camera, aspect_ratio),
+            transform_normals: Transform::for_normals(&self.camera),
+            transform_view: Transform::for_view(&self.camera),
+            eye: eye.map(|value| value.into_f32()),
+            viewport_size: [bounds.width.round(), bounds.height.round()],
+            edge_width: self.draw_config.edge_width
+                * viewport.scale_factor() as f32,
+            crease_cos: self.draw_config.crease_angle.cos() as f32,
+            lighting: self
+                .draw_config
+                .lighting
+                .uniforms(&self.camera, &self.draw_config.material),
+        };
+
+        let skybox_uniforms = SkyboxUniforms::new(
+            &self.camera,
+            aspect_ratio,
+            &self.draw_config.background,
         );
+
+        pipeline.set_model(device, queue, &self.model);
+        pipeline.update(device, queue, bounds, &uniforms, &skybox_uniforms);
     }
 
     fn render(
@@ -116,59 +786,92 @@ impl shader::Primitive for Primitive {
         target: &wgpu::TextureView,
         clip_bounds: &Rectangle<u32>,
     ) {
-        // println!("render...");
-        // println!("clip_bounds: {:?}", clip_bounds);        
-
         // At this point our pipeline should always be initialized
-        let pipeline = storage.get::<Pipeline>().unwrap();
+        let (_, pipeline) =
+            &storage.get::<ViewerPipelines>().unwrap().0[&*self.id.0];
 
-        // // Render primitive
-        pipeline.render(
-            target,
-            encoder,
-            *clip_bounds,
-        );
+        pipeline.render(target, encoder, *clip_bounds, &self.draw_config);
+    }
+}
+
+/// The GPU state of all viewer widgets, by their id
+#[derive(Default)]
+struct ViewerPipelines(HashMap<u64, (Weak<u64>, Pipeline)>);
+
+/// The environment, shared by all viewer widgets
+///
+/// Prefiltering the environment map takes a while, so a black placeholder is
+/// used, until a viewer shows the environment or lights the model with it.
+struct SharedEnvironment {
+    current: Arc<Environment>,
+
+    /// Whether loading the bundled environment was attempted
+    is_loaded: bool,
+}
+
+impl SharedEnvironment {
+    fn new(device: &wgpu::Device) -> Self {
+        Self {
+            current: Arc::new(Environment::placeholder(device)),
+            is_loaded: false,
+        }
+    }
+
+    /// Replace the placeholder with the bundled environment
+    ///
+    /// If the environment fails to load, the placeholder stays, and loading
+    /// isn't attempted again.
+    fn load(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        if self.is_loaded {
+            return;
+        }
+        self.is_loaded = true;
+
+        match Environment::load_bundled(device, queue) {
+            Ok(environment) => self.current = Arc::new(environment),
+            Err(error) => {
+                tracing::error!("Failed to load the environment: {error}");
+            }
+        }
     }
 }
 
 pub struct Pipeline {
-    camera: Camera,
-    model: Arc<fj_interop::Model>,
-    depth_view: wgpu::TextureView,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    targets: RenderTargets,
+    blit: Blit,
     uniform_buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
     geometries: Geometries,
     pipelines: Pipelines,
-    config: DrawConfig,
+    skybox: Skybox,
+
+    /// The environment that [`Pipeline::bind_group`] and
+    /// [`Pipeline::skybox`] sample
+    environment: Arc<Environment>,
+
+    /// The model that [`Pipeline::geometries`] were created from
+    model: Weak<fj_interop::Model>,
 }
 
 impl Pipeline {
     pub fn new(
         device: &wgpu::Device,
-        queue: &wgpu::Queue,
         format: wgpu::TextureFormat,
         target_size: Size<u32>,
-        model: Arc<fj_interop::Model>,
+        model: &Arc<fj_interop::Model>,
+        environment: &Arc<Environment>,
+        sample_count: u32,
     ) -> Self {
-        let width = target_size.width;
-        let height = target_size.height;
-
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: None,
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: SAMPLE_COUNT,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Depth32Float,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            view_formats: &[],
-        });
-
-        let depth_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let blit = Blit::new(device, format);
+        let targets = RenderTargets::new(
+            device,
+            target_size,
+            format,
+            sample_count,
+            &blit,
+        );
 
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: None,
@@ -185,6 +888,33 @@ impl Pipeline {
                     min_binding_size: wgpu::BufferSize::new(size_of::<Uniforms>() as u64),
                 },
                 count: None,
+            },
+            // The convolutions of the environment, for image-based lighting
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::Cube,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::Cube,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
             }],
             label: None,
         });
@@ -197,6 +927,18 @@ impl Pipeline {
                     offset: 0,
                     size: None,
                 }),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(&environment.specular.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(&environment.irradiance.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::Sampler(&environment.specular.sampler),
             }],
             label: None,
         });
@@ -205,7 +947,13 @@ impl Pipeline {
             device,
             &bind_group_layout,
             format,
-            wgpu::Features::empty(),
+            sample_count,
+        );
+        let skybox = Skybox::new(
+            device,
+            format,
+            sample_count,
+            &environment.skybox,
         );
 
 
@@ -216,62 +964,128 @@ impl Pipeline {
         // // );
         // // let aabb = model.aabb.as_ref().map(|shape| shape.aabb).unwrap_or_default();
 
-        let mut camera = Camera::default();
-        camera.update_planes(&model.aabb);
-
-        let aspect_ratio = f64::from(width) / f64::from(height);
-        let uniforms = Uniforms {
-            transform: Transform::for_vertices(&camera, aspect_ratio),
-            transform_normals: Transform::for_normals(&camera),
-        };
-
-        queue.write_buffer(&uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
-
-
         let geometries = Geometries::new(device, &((&model.mesh).into()));
 
         Self {
-            camera,
-            model: model.clone(),
-            depth_view,
+            format,
+            sample_count,
+            targets,
+            blit,
             uniform_buffer,
             bind_group,
             geometries,
             pipelines,
-            config: DrawConfig::default(),
+            skybox,
+            environment: Arc::clone(environment),
+            model: Arc::downgrade(model),
         }
     }
 
+    /// Upload the geometry of the model, if it isn't the one shown already
+    pub fn set_model(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        model: &Arc<fj_interop::Model>,
+    ) {
+        // The weak reference keeps the allocation alive, so a new model can't
+        // end up at the same address as the previous one.
+        if self.model.as_ptr() == Arc::as_ptr(model) {
+            return;
+        }
+
+        self.geometries.update(device, queue, &((&model.mesh).into()));
+        self.model = Arc::downgrade(model);
+    }
+
+    /// Prepare for rendering a new frame
+    ///
+    /// `bounds` are the bounds of the widget, in physical pixels. The render
+    /// targets are recreated, if their size has changed since the last frame,
+    /// which happens when the widget is resized or the scale factor changes.
     pub fn update(
         &mut self,
-        _device: &wgpu::Device,
-        _queue: &wgpu::Queue,
-        target_size: Size<u32>,
-        // uniforms: &Uniforms,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bounds: Rectangle,
+        uniforms: &Uniforms,
+        skybox_uniforms: &SkyboxUniforms,
     ) {
+        let size = Size::new(
+            bounds.width.round() as u32,
+            bounds.height.round() as u32,
+        );
+        if size != self.targets.size {
+            self.targets = RenderTargets::new(
+                device,
+                size,
+                self.format,
+                self.sample_count,
+                &self.blit,
+            );
+        }
+
+        self.blit
+            .set_origin(queue, bounds.x.round(), bounds.y.round());
+
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[*uniforms]),
+        );
+        self.skybox.update(queue, skybox_uniforms);
     }
 
     pub fn render(
         &self,
         target: &wgpu::TextureView,
         encoder: &mut wgpu::CommandEncoder,
-        viewport: Rectangle<u32>,
+        clip_bounds: Rectangle<u32>,
+        config: &DrawConfig,
     ) {
+        if clip_bounds.width == 0 || clip_bounds.height == 0 {
+            return;
+        }
+
+        // With multisampling, only the resolved image is needed afterwards.
+        let color_view = &self.targets.color_view;
+        let (view, resolve_target, store) = match &self.targets.msaa_view {
+            Some(msaa_view) => {
+                (msaa_view, Some(color_view), wgpu::StoreOp::Discard)
+            }
+            None => (color_view, None, wgpu::StoreOp::Store),
+        };
+
+        // Solid backgrounds are drawn by clearing the target. The other kinds
+        // are drawn over whatever it is cleared to.
+        let clear_color = match config.background {
+            Background::Solid([r, g, b]) => wgpu::Color {
+                r: r.into(),
+                g: g.into(),
+                b: b.into(),
+                a: 1.,
+            },
+            Background::Gradient { .. } | Background::Environment => {
+                wgpu::Color::BLACK
+            }
+        };
+
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: target,
-                    resolve_target: None,
+                    view,
+                    resolve_target,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::GREEN),
-                        store: wgpu::StoreOp::Discard,
+                        load: wgpu::LoadOp::Clear(clear_color),
+                        store,
                     },
                 })],
                 // depth_stencil_attachment: None,
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.depth_view,
+                    view: &self.targets.depth_view,
                     depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
+                        // Reverse-Z: 0 is infinitely far away.
+                        load: wgpu::LoadOp::Clear(0.0),
                         store: wgpu::StoreOp::Store,
                     }),
                     stencil_ops: None,
@@ -279,26 +1093,34 @@ impl Pipeline {
                 ..Default::default()
             });
 
-            render_pass.set_scissor_rect(
-                viewport.x,
-                viewport.y,
-                viewport.width,
-                viewport.height,
-            );
+            if !matches!(config.background, Background::Solid(_)) {
+                self.skybox.draw(&mut render_pass);
+            }
+
             render_pass.set_bind_group(0, &self.bind_group, &[]);
 
             let drawables = Drawables::new(&self.geometries, &self.pipelines);
 
-            // if self.config.draw_model {
+            if config.draw_model {
                 drawables.model.draw(&mut render_pass);
-            // }
+            }
 
-            // if let Some(drawable) = drawables.mesh {
-            //     if self.config.draw_mesh {
-            //         drawable.draw(&mut render_pass);
-            //     }
-            // }
+            if config.draw_mesh {
+                drawables.mesh.draw(&mut render_pass);
+            }
+
+            if config.draw_edges {
+                drawables.feature_edges.draw(&mut render_pass);
+            }
         }
+
+        self.blit.draw(
+            encoder,
+            &self.targets.blit_bind_group,
+            target,
+            clip_bounds,
+        );
+
         // self.navigation_cube_renderer.draw(
         //     target,
         //     encoder,
@@ -308,3 +1130,94 @@ impl Pipeline {
         // );
     }
 }
+
+/// The textures that the model is rendered into
+///
+/// They have the size of the widget. After rendering, the color target is
+/// copied into the widget's area of the actual render target.
+struct RenderTargets {
+    /// The size of the textures, in physical pixels
+    size: Size<u32>,
+
+    depth_view: wgpu::TextureView,
+
+    /// The multisampled color target, which is resolved into `color_view`
+    ///
+    /// This is `None`, if there is only one sample per pixel, and rendering
+    /// goes straight into `color_view`.
+    msaa_view: Option<wgpu::TextureView>,
+
+    color_view: wgpu::TextureView,
+    blit_bind_group: wgpu::BindGroup,
+}
+
+impl RenderTargets {
+    fn new(
+        device: &wgpu::Device,
+        size: Size<u32>,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        blit: &Blit,
+    ) -> Self {
+        let depth_view = create_render_target(
+            device,
+            size,
+            DEPTH_FORMAT,
+            sample_count,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+        );
+        let msaa_view = (sample_count > 1).then(|| {
+            create_render_target(
+                device,
+                size,
+                format,
+                sample_count,
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+            )
+        });
+        let color_view = create_render_target(
+            device,
+            size,
+            format,
+            1,
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING,
+        );
+        let blit_bind_group = blit.bind_group(device, &color_view);
+
+        Self {
+            size,
+            depth_view,
+            msaa_view,
+            color_view,
+            blit_bind_group,
+        }
+    }
+}
+
+/// Create a texture that can be rendered to, and return a view of it
+fn create_render_target(
+    device: &wgpu::Device,
+    size: Size<u32>,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    usage: wgpu::TextureUsages,
+) -> wgpu::TextureView {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        // Textures can't be empty, even if the widget is.
+        size: wgpu::Extent3d {
+            width: size.width.max(1),
+            height: size.height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage,
+        view_formats: &[],
+    });
+
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
@@ -0,0 +1,321 @@
+//! Models that are built from a set of numeric parameters
+
+use std::ops::RangeInclusive;
+use std::panic::{self, AssertUnwindSafe};
+
+use fj_core::algorithms::approx::Tolerance;
+use fj_core::algorithms::bounding_volume::BoundingVolume;
+use fj_core::algorithms::triangulate::Triangulate;
+use fj_core::objects::Solid;
+use fj_core::validate::{ShellValidationError, SolidValidationError};
+use fj_core::validation::ValidationError;
+use fj_core::Core;
+use fj_math::{Aabb, Point, Scalar};
+
+use crate::error::error_chain;
+
+mod panel;
+
+pub use self::panel::{PanelMessage, ParameterPanel};
+
+/// A model that is built from named numeric parameters
+pub trait ParametricModel: Send + Sync {
+    /// The parameters of the model, in the order they are presented in
+    fn parameters(&self) -> Vec<Parameter>;
+
+    /// Build the model from the given parameter values
+    fn build(&self, parameters: &Parameters, core: &mut Core) -> Solid;
+
+    /// Points of interest on the model, to be labeled in the UI
+    ///
+    /// Models don't have any labels by default.
+    fn labels(
+        &self,
+        _parameters: &Parameters,
+    ) -> Vec<(&'static str, Point<3>)> {
+        Vec::new()
+    }
+}
+
+/// A numeric parameter of a [`ParametricModel`]
+#[derive(Clone, Debug)]
+pub struct Parameter {
+    name: &'static str,
+    unit: &'static str,
+    range: RangeInclusive<f64>,
+    default: f64,
+}
+
+impl Parameter {
+    /// Declare a parameter with its default value and the range of valid
+    /// values
+    pub fn new(
+        name: &'static str,
+        default: f64,
+        range: RangeInclusive<f64>,
+    ) -> Self {
+        Self {
+            name,
+            unit: "",
+            range,
+            default,
+        }
+    }
+
+    /// Sets the unit that values of the parameter are shown in
+    pub fn unit(mut self, unit: &'static str) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Returns the value, moved into the range of valid values
+    fn clamp(&self, value: f64) -> f64 {
+        value.clamp(*self.range.start(), *self.range.end())
+    }
+}
+
+/// The values of the parameters of a [`ParametricModel`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameters(Vec<(&'static str, f64)>);
+
+impl Parameters {
+    /// The default values of the given parameters
+    pub fn defaults(parameters: &[Parameter]) -> Self {
+        Self(
+            parameters
+                .iter()
+                .map(|parameter| (parameter.name, parameter.default))
+                .collect(),
+        )
+    }
+
+    /// Returns the value of the parameter with the given name
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the model didn't declare a parameter with that name.
+    pub fn get(&self, name: &str) -> f64 {
+        self.0
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| *value)
+            .unwrap_or_else(|| panic!("Unknown parameter `{name}`"))
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        self.0[index].1 = value;
+    }
+}
+
+/// Build a parametric model and triangulate it for display
+///
+/// Fails, if the model doesn't pass validation. Some invalid parameter values
+/// make `fj_core` panic, instead of reporting a validation error. These panics
+/// are caught and returned as errors too.
+pub fn build(
+    model: &dyn ParametricModel,
+    parameters: &Parameters,
+) -> Result<fj_interop::Model, BuildError> {
+    panic::catch_unwind(AssertUnwindSafe(|| build_inner(model, parameters)))
+        .unwrap_or_else(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown error".to_string());
+
+            Err(BuildError::Panicked(message))
+        })
+}
+
+fn build_inner(
+    model: &dyn ParametricModel,
+    parameters: &Parameters,
+) -> Result<fj_interop::Model, BuildError> {
+    let mut core = Core::new();
+    let solid = model.build(parameters, &mut core);
+
+    if let Err(errors) = core.layers.validation.take_errors() {
+        // Validation errors refer to objects of the core they came from, so
+        // they are turned into messages before the core goes away.
+        let messages =
+            errors.0.iter().map(|error| error_chain(error)).collect();
+        let points = errors.0.iter().flat_map(error_points).collect();
+        return Err(BuildError::Invalid { messages, points });
+    }
+
+    let aabb = solid.aabb(&core.layers.geometry).unwrap_or(Aabb {
+        min: Point::origin(),
+        max: Point::origin(),
+    });
+
+    let mut min_extent = Scalar::MAX;
+    for extent in aabb.size().components {
+        if extent > Scalar::ZERO && extent < min_extent {
+            min_extent = extent;
+        }
+    }
+
+    let tolerance = min_extent / Scalar::from_f64(1000.);
+    let tolerance = Tolerance::from_scalar(tolerance).unwrap();
+
+    let mesh = (&solid, tolerance).triangulate(&mut core);
+    Ok(fj_interop::Model { mesh, aabb })
+}
+
+/// The positions in the model that a validation error refers to
+///
+/// Only some errors carry positions. Others just refer to objects, like
+/// half-edges, which are of no use once their core is gone.
+fn error_points(error: &ValidationError) -> Vec<Point<3>> {
+    match error {
+        ValidationError::Solid(
+            SolidValidationError::DistinctVerticesCoincide {
+                position_a,
+                position_b,
+                ..
+            }
+            | SolidValidationError::IdenticalVerticesNotCoincident {
+                position_a,
+                position_b,
+                ..
+            },
+        ) => vec![*position_a, *position_b],
+        ValidationError::Shell(
+            ShellValidationError::CurveCoordinateSystemMismatch(mismatches),
+        ) => mismatches
+            .iter()
+            .flat_map(|mismatch| [mismatch.point_a, mismatch.point_b])
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// An error building a [`ParametricModel`]
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum BuildError {
+    /// The model failed validation
+    #[error("Model is invalid")]
+    Invalid {
+        /// A message for each validation error
+        messages: Vec<String>,
+
+        /// The positions that the validation errors refer to, if any
+        points: Vec<Point<3>>,
+    },
+
+    /// Building the model panicked
+    #[error("Building the model failed")]
+    Panicked(String),
+}
+
+impl BuildError {
+    /// The individual problems that make up the error
+    pub fn details(&self) -> &[String] {
+        match self {
+            Self::Invalid { messages, .. } => messages,
+            Self::Panicked(message) => std::slice::from_ref(message),
+        }
+    }
+
+    /// The positions in the model where the problems are
+    ///
+    /// These can be highlighted in a viewer that shows the last valid model.
+    pub fn points(&self) -> &[Point<3>] {
+        match self {
+            Self::Invalid { points, .. } => points,
+            Self::Panicked(_) => &[],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fj_core::objects::{Region, Sketch, Solid};
+    use fj_core::operations::build::{BuildRegion, BuildSketch};
+    use fj_core::operations::insert::Insert;
+    use fj_core::operations::sweep::SweepSketch;
+    use fj_core::operations::update::UpdateSketch;
+    use fj_core::Core;
+    use fj_math::{Scalar, Vector};
+
+    use super::{build, BuildError, Parameter, Parameters, ParametricModel};
+
+    /// Two identical cubes, in the same place
+    ///
+    /// Their vertices are distinct, but coincide, which fails validation.
+    struct OverlappingCubes;
+
+    impl ParametricModel for OverlappingCubes {
+        fn parameters(&self) -> Vec<Parameter> {
+            Vec::new()
+        }
+
+        fn build(&self, _: &Parameters, core: &mut Core) -> Solid {
+            let mut cube = || {
+                let surface = core.layers.objects.surfaces.xy_plane();
+                Sketch::empty()
+                    .add_regions(
+                        [Region::polygon(
+                            [[0., 0.], [1., 0.], [1., 1.], [0., 1.]],
+                            core,
+                        )],
+                        core,
+                    )
+                    .sweep_sketch(surface, Vector::from([0., 0., 1.]), core)
+            };
+
+            let [a, b] = [cube(), cube()];
+            Solid::new(a.shells().iter().chain(b.shells()).cloned())
+                .insert(core)
+                .clone_object()
+        }
+    }
+
+    struct Panicking;
+
+    impl ParametricModel for Panicking {
+        fn parameters(&self) -> Vec<Parameter> {
+            Vec::new()
+        }
+
+        fn build(&self, _: &Parameters, _: &mut Core) -> Solid {
+            panic!("Out of cheese");
+        }
+    }
+
+    #[test]
+    fn invalid_model() {
+        let error =
+            build(&OverlappingCubes, &Parameters(Vec::new())).unwrap_err();
+
+        let BuildError::Invalid { messages, points } = &error else {
+            panic!("Expected the model to be invalid: {error:?}");
+        };
+        assert!(!messages.is_empty());
+        assert!(!points.is_empty());
+
+        // The points are where the vertices of the cubes are.
+        for point in points {
+            assert!(point
+                .coords
+                .components
+                .iter()
+                .all(|&c| c == Scalar::ZERO || c == Scalar::ONE));
+        }
+        assert_eq!(error.details(), messages.as_slice());
+        assert_eq!(error.points(), points.as_slice());
+    }
+
+    #[test]
+    fn panicking_model() {
+        let error = build(&Panicking, &Parameters(Vec::new())).unwrap_err();
+
+        assert!(matches!(
+            &error,
+            BuildError::Panicked(message) if message == "Out of cheese",
+        ));
+        assert_eq!(error.details(), ["Out of cheese"]);
+        assert!(error.points().is_empty());
+    }
+}
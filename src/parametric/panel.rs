@@ -0,0 +1,135 @@
+use iced::widget::{column, row, slider, text, text_input};
+use iced::{Center, Element};
+
+use super::{Parameter, Parameters};
+
+/// A panel with a slider and a text input for each parameter of a model
+pub struct ParameterPanel {
+    parameters: Vec<Parameter>,
+    values: Parameters,
+
+    /// The contents of the text inputs
+    ///
+    /// These are kept separately from the values, so the user can type
+    /// numbers that aren't valid yet, like "1." on the way to "1.5".
+    inputs: Vec<String>,
+}
+
+/// A change made in a [`ParameterPanel`]
+#[derive(Clone, Debug)]
+pub enum PanelMessage {
+    SliderChanged(usize, f64),
+    InputChanged(usize, String),
+}
+
+impl ParameterPanel {
+    /// Create a panel for the given parameters, set to their default values
+    pub fn new(parameters: Vec<Parameter>) -> Self {
+        let values = Parameters::defaults(&parameters);
+        let inputs = values.0.iter().map(|(_, value)| format(*value)).collect();
+
+        Self {
+            parameters,
+            values,
+            inputs,
+        }
+    }
+
+    /// Access the current parameter values
+    pub fn values(&self) -> &Parameters {
+        &self.values
+    }
+
+    /// Handle a change made in the panel
+    ///
+    /// Returns `true`, if a parameter value has changed and the model needs to
+    /// be rebuilt.
+    pub fn update(&mut self, message: PanelMessage) -> bool {
+        let (index, value) = match message {
+            PanelMessage::SliderChanged(index, value) => {
+                self.inputs[index] = format(value);
+                (index, value)
+            }
+            PanelMessage::InputChanged(index, input) => {
+                let value = input.trim().parse::<f64>();
+                self.inputs[index] = input;
+
+                match value {
+                    Ok(value) if value.is_finite() => {
+                        (index, self.parameters[index].clamp(value))
+                    }
+                    _ => return false,
+                }
+            }
+        };
+
+        if self.values.0[index].1 == value {
+            return false;
+        }
+        self.values.set(index, value);
+
+        true
+    }
+
+    pub fn view(&self) -> Element<'_, PanelMessage> {
+        let rows = self.parameters.iter().enumerate().map(|(i, parameter)| {
+            let value = self.values.0[i].1;
+            let step = (parameter.range.end() - parameter.range.start()) / 100.;
+
+            row![
+                text(parameter.name).width(60),
+                slider(parameter.range.clone(), value, move |value| {
+                    PanelMessage::SliderChanged(i, value)
+                })
+                .step(step)
+                .width(150),
+                text_input("", &self.inputs[i])
+                    .on_input(move |input| PanelMessage::InputChanged(i, input))
+                    .width(70),
+                text(parameter.unit).width(30),
+            ]
+            .spacing(10)
+            .align_y(Center)
+            .into()
+        });
+
+        column(rows).spacing(5).into()
+    }
+}
+
+fn format(value: f64) -> String {
+    format!("{value:.2}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parametric::Parameter;
+
+    use super::{PanelMessage, ParameterPanel};
+
+    fn panel() -> ParameterPanel {
+        ParameterPanel::new(vec![Parameter::new("x", 1.0, 0.5..=2.0)])
+    }
+
+    #[test]
+    fn slider_updates_value_and_input() {
+        let mut panel = panel();
+
+        assert!(panel.update(PanelMessage::SliderChanged(0, 1.5)));
+        assert_eq!(panel.values().get("x"), 1.5);
+        assert_eq!(panel.inputs[0], "1.50");
+    }
+
+    #[test]
+    fn input_is_kept_until_it_is_a_valid_number() {
+        let mut panel = panel();
+
+        assert!(!panel.update(PanelMessage::InputChanged(0, "1e".into())));
+        assert_eq!(panel.values().get("x"), 1.0);
+        assert_eq!(panel.inputs[0], "1e");
+
+        assert!(panel.update(PanelMessage::InputChanged(0, "1e1".into())));
+        assert_eq!(panel.values().get("x"), 2.0);
+        assert_eq!(panel.inputs[0], "1e1");
+    }
+}